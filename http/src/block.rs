@@ -5,11 +5,15 @@ pub(crate) fn block<F, T>(fut: F) -> T
 where
     F: Future<Output = T>,
 {
-    let rt = tokio::runtime::Builder::new_current_thread()
+    runtime().block_on(fut)
+}
+
+#[cfg(feature = "blocking")]
+pub(crate) fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
-        .expect("build shell runtime");
-    rt.block_on(fut)
+        .expect("build shell runtime")
 }
 
 pub(crate) fn spawn<F: Future + Send + 'static>(task: F)
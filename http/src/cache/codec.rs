@@ -0,0 +1,172 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+/// turns cache entries into bytes and back
+///
+/// [`crate::cache::RedisCache`] stores [`Json`] by default. The `msgpack`
+/// feature adds the denser `MessagePack`, and other formats like bincode can
+/// be stored by implementing this trait:
+///
+/// ```ignore
+/// use gw2lib::cache::Codec;
+///
+/// struct Bincode;
+///
+/// impl Codec for Bincode {
+///     fn encode<T: Serialize>(&self, value: &T) -> Option<Vec<u8>> {
+///         bincode::serialize(value).ok()
+///     }
+///
+///     fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Option<T> {
+///         bincode::deserialize(bytes).ok()
+///     }
+/// }
+/// ```
+///
+/// Entries that fail to decode are treated as missing, so switching the codec
+/// only costs the entries written with the old one.
+pub trait Codec: Send + Sync {
+    fn encode<T: Serialize>(&self, value: &T) -> Option<Vec<u8>>;
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Option<T>;
+}
+
+/// compact `serde_json`, the default [`Codec`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json;
+
+impl Codec for Json {
+    fn encode<T: Serialize>(&self, value: &T) -> Option<Vec<u8>> {
+        serde_json::to_vec(value).ok()
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Option<T> {
+        serde_json::from_slice(bytes).ok()
+    }
+}
+
+/// MessagePack with field names, usually a good deal smaller than [`Json`]
+///
+/// Fields are stored by name rather than position, so entries stay readable
+/// when the model gains fields.
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePack;
+
+#[cfg(feature = "msgpack")]
+impl Codec for MessagePack {
+    fn encode<T: Serialize>(&self, value: &T) -> Option<Vec<u8>> {
+        rmp_serde::to_vec_named(value).ok()
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Option<T> {
+        rmp_serde::from_slice(bytes).ok()
+    }
+}
+
+/// a compression algorithm applied by [`Compressed`]
+///
+/// The `deflate` feature adds [`Deflate`]. Other algorithms can be plugged in
+/// by implementing this trait:
+///
+/// ```ignore
+/// use gw2lib::cache::Compression;
+///
+/// struct Zstd;
+///
+/// impl Compression for Zstd {
+///     fn compress(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+///         zstd::bulk::compress(bytes, 3).ok()
+///     }
+///
+///     fn decompress(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+///         zstd::stream::decode_all(bytes).ok()
+///     }
+/// }
+/// ```
+pub trait Compression: Send + Sync {
+    fn compress(&self, bytes: &[u8]) -> Option<Vec<u8>>;
+
+    fn decompress(&self, bytes: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// compresses the output of another [`Codec`]
+///
+/// ## Example
+/// ```ignore
+/// let cache = RedisCache::new(client).codec(Compressed::new(Json, Deflate::default()));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Compressed<C, Z> {
+    codec: C,
+    compression: Z,
+}
+
+impl<C, Z> Compressed<C, Z> {
+    pub fn new(codec: C, compression: Z) -> Self {
+        Self { codec, compression }
+    }
+}
+
+impl<C: Codec, Z: Compression> Codec for Compressed<C, Z> {
+    fn encode<T: Serialize>(&self, value: &T) -> Option<Vec<u8>> {
+        let bytes = self.codec.encode(value)?;
+        self.compression.compress(&bytes)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Option<T> {
+        let bytes = self.compression.decompress(bytes)?;
+        self.codec.decode(&bytes)
+    }
+}
+
+/// deflate from `flate2`, a fast pure rust compression
+///
+/// ## Example
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use gw2lib::{
+///     cache::{Compressed, Deflate, Json, RedisCache},
+///     Client,
+/// };
+///
+/// let redis = redis::Client::open("redis://localhost").unwrap();
+/// let cache = RedisCache::new(redis).codec(Compressed::new(Json, Deflate::default()));
+/// let client = Client::default().cache(Arc::new(cache));
+/// ```
+#[cfg(feature = "deflate")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Deflate {
+    level: flate2::Compression,
+}
+
+#[cfg(feature = "deflate")]
+impl Deflate {
+    /// compresses with `level`, from 0 (none) to 9 (best)
+    pub fn new(level: u32) -> Self {
+        Self {
+            level: flate2::Compression::new(level.min(9)),
+        }
+    }
+}
+
+#[cfg(feature = "deflate")]
+impl Compression for Deflate {
+    fn compress(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), self.level);
+        encoder.write_all(bytes).ok()?;
+        encoder.finish().ok()
+    }
+
+    fn decompress(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        use std::io::Read;
+
+        let mut out = Vec::new();
+        flate2::read::DeflateDecoder::new(bytes)
+            .read_to_end(&mut out)
+            .ok()?;
+        Some(out)
+    }
+}
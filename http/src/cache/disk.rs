@@ -0,0 +1,299 @@
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    hash::Hash,
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use gw2lib_model::{Endpoint, Language};
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Deserialize, Serialize,
+};
+use tokio::fs;
+
+use crate::cache::{
+    gen_key, key_identifier, stale_retention, Cache, CacheEntry, Validators, KEY_PREFIX,
+};
+
+const STATIC: &str = "static";
+const AUTH: &str = "auth";
+
+/// a cache storing every entry as a file in a directory
+///
+/// Entries survive restarts, so tools can keep large endpoints like
+/// `v2/items` around between runs without running Redis.
+/// Static and authenticated entries live in separate subdirectories, which
+/// [`Cache::wipe_static`] and [`Cache::wipe_authenticated`] remove.
+///
+/// Several clients, even in different processes, can share a directory.
+///
+/// ## Example
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use gw2lib::{cache::DiskCache, Client};
+///
+/// let cache = DiskCache::new("cache/gw2lib").unwrap();
+/// let client = Client::default().cache(Arc::new(cache));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+/// the contents of an entry file
+///
+/// Files are named after a hash of the key, so the key is stored as well to
+/// tell collisions apart. The identifier of authenticated entries is stored
+/// on its own for [`Cache::wipe_identifier`].
+#[derive(Serialize, Deserialize)]
+struct Stored<'a, T> {
+    key: Cow<'a, str>,
+    #[serde(default)]
+    identifier: Option<Cow<'a, str>>,
+    entry: CacheEntry<T>,
+}
+
+#[async_trait]
+impl Cache for DiskCache {
+    async fn insert<T, I, E, A>(
+        &self,
+        id: &I,
+        endpoint: &T,
+        expiring: NaiveDateTime,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        self.insert_validated::<T, I, E, A>(
+            id,
+            endpoint,
+            expiring,
+            &Validators::default(),
+            lang,
+            auth,
+        )
+        .await
+    }
+
+    async fn get<T, I, E, A>(&self, id: &I, lang: Language, auth: &Option<A>) -> Option<T>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let entry = self.get_stale::<T, I, E, A>(id, lang, auth).await?;
+        (Utc::now().naive_utc() < entry.expiring).then_some(entry.value)
+    }
+
+    async fn insert_validated<T, I, E, A>(
+        &self,
+        id: &I,
+        endpoint: &T,
+        expiring: NaiveDateTime,
+        validators: &Validators,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let key = gen_key::<E, I, A>(KEY_PREFIX, id, lang, auth);
+        let path = self.path::<E>(&key);
+        let stored = Stored {
+            key: Cow::Borrowed(&key),
+            identifier: auth
+                .as_ref()
+                .filter(|_| E::AUTHENTICATED)
+                .map(|auth| Cow::Owned(auth.to_string())),
+            entry: CacheEntry {
+                value: endpoint,
+                expiring,
+                validators: validators.clone(),
+            },
+        };
+        if let Ok(contents) = serde_json::to_vec(&stored) {
+            write_atomic(&path, contents).await.ok();
+        }
+    }
+
+    async fn get_stale<T, I, E, A>(
+        &self,
+        id: &I,
+        lang: Language,
+        auth: &Option<A>,
+    ) -> Option<CacheEntry<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let key = gen_key::<E, I, A>(KEY_PREFIX, id, lang, auth);
+        let contents = fs::read(self.path::<E>(&key)).await.ok()?;
+        let stored: Stored<T> = serde_json::from_slice(&contents).ok()?;
+        (stored.key == key).then_some(stored.entry)
+    }
+
+    async fn cleanup(&self) {
+        let now = Utc::now().naive_utc();
+        self.cleanup_dir(STATIC, now).await;
+        self.cleanup_dir(AUTH, now).await;
+    }
+
+    async fn wipe_static(&self) {
+        self.wipe_dir(STATIC).await;
+    }
+
+    async fn wipe_authenticated(&self) {
+        self.wipe_dir(AUTH).await;
+    }
+
+    async fn remove<T, I, E, A>(&self, id: &I, lang: Language, auth: &Option<A>)
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let key = gen_key::<E, I, A>(KEY_PREFIX, id, lang, auth);
+        fs::remove_file(self.path::<E>(&key)).await.ok();
+    }
+
+    async fn wipe_endpoint<E: Endpoint>(&self) {
+        let (dir, kind) = if E::AUTHENTICATED {
+            (AUTH, "auth")
+        } else {
+            (STATIC, "static")
+        };
+        let prefix = format!("{KEY_PREFIX}_{kind}_");
+        let name = format!("{}_", E::NAME);
+        self.remove_where(dir, |stored| {
+            let key = stored.key.strip_prefix(&prefix);
+            // authenticated keys have the identifier in front of the endpoint
+            let key = match &stored.identifier {
+                Some(auth) => key
+                    .and_then(|key| key.strip_prefix(&key_identifier(auth)))
+                    .and_then(|key| key.strip_prefix('_')),
+                None => key,
+            };
+            key.is_some_and(|key| key.starts_with(&name))
+        })
+        .await;
+    }
+
+    async fn wipe_identifier<A>(&self, auth: &A)
+    where
+        A: Display + Hash + Sync + 'static,
+    {
+        let auth = auth.to_string();
+        self.remove_where(AUTH, |stored| {
+            stored.identifier.as_deref() == Some(auth.as_str())
+        })
+        .await;
+    }
+}
+
+impl DiskCache {
+    /// stores the cache in `dir`, creating it if it doesn't exist
+    ///
+    /// Entries already in `dir` are picked up again.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(dir.join(STATIC))?;
+        std::fs::create_dir_all(dir.join(AUTH))?;
+        Ok(Self { dir })
+    }
+
+    fn path<E: Endpoint>(&self, key: &str) -> PathBuf {
+        let dir = if E::AUTHENTICATED { AUTH } else { STATIC };
+        self.dir.join(dir).join(format!("{:016x}.json", fnv1a(key)))
+    }
+
+    async fn wipe_dir(&self, dir: &str) {
+        let dir = self.dir.join(dir);
+        fs::remove_dir_all(&dir).await.ok();
+        fs::create_dir_all(&dir).await.ok();
+    }
+
+    /// removes entries past their retention, unreadable files and leftovers
+    /// of interrupted writes
+    async fn cleanup_dir(&self, dir: &str, now: NaiveDateTime) {
+        let mut files = match fs::read_dir(self.dir.join(dir)).await {
+            Ok(files) => files,
+            Err(_) => return,
+        };
+        while let Ok(Some(file)) = files.next_entry().await {
+            let path = file.path();
+            let expired = if path.extension().is_some_and(|ext| ext == "json") {
+                fs::read(&path)
+                    .await
+                    .ok()
+                    .and_then(|x| serde_json::from_slice::<Stored<IgnoredAny>>(&x).ok())
+                    .is_none_or(|x| now >= x.entry.expiring + stale_retention())
+            } else {
+                file.metadata()
+                    .await
+                    .and_then(|x| x.modified())
+                    .ok()
+                    .and_then(|x| SystemTime::now().duration_since(x).ok())
+                    .is_some_and(|age| age > stale_retention().to_std().unwrap_or_default())
+            };
+            if expired {
+                fs::remove_file(&path).await.ok();
+            }
+        }
+    }
+
+    /// removes the entries in `dir` matching `f`
+    async fn remove_where(&self, dir: &str, f: impl Fn(&Stored<IgnoredAny>) -> bool) {
+        let mut files = match fs::read_dir(self.dir.join(dir)).await {
+            Ok(files) => files,
+            Err(_) => return,
+        };
+        while let Ok(Some(file)) = files.next_entry().await {
+            let path = file.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let matches = fs::read(&path)
+                .await
+                .ok()
+                .and_then(|x| serde_json::from_slice::<Stored<IgnoredAny>>(&x).ok())
+                .is_some_and(|x| f(&x));
+            if matches {
+                fs::remove_file(&path).await.ok();
+            }
+        }
+    }
+}
+
+/// writes into a temporary file first, so readers never see partial entries
+async fn write_atomic(path: &Path, contents: Vec<u8>) -> io::Result<()> {
+    let tmp = path.with_extension(format!("{:016x}.tmp", fastrand::u64(..)));
+    fs::write(&tmp, contents).await?;
+    let res = fs::rename(&tmp, path).await;
+    if res.is_err() {
+        fs::remove_file(&tmp).await.ok();
+    }
+    res
+}
+
+/// 64 bit FNV-1a, stable across runs and platforms unlike the std hashers
+fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -1,27 +1,134 @@
 use std::{
     any::{Any, TypeId},
-    collections::hash_map::RandomState,
+    collections::{hash_map::RandomState, HashMap},
     hash::{BuildHasher, Hash, Hasher},
+    io,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
 };
 
 use async_trait::async_trait;
 use chrono::{NaiveDateTime, Utc};
-use dashmap::{mapref::entry::Entry, DashMap};
+use dashmap::{mapref::entry::Entry as MapEntry, DashMap};
 use gw2lib_model::{Endpoint, Language};
+use serde::Serialize;
 
-use crate::cache::Cache;
+use crate::cache::{stale_retention, Cache, CacheEntry, Validators};
 
+type Key = (TypeId, u64);
+
+/// a cache keeping entries in memory
+///
+/// Unbounded by default. Limits can be set separately for static and
+/// authenticated entries, and for single endpoints. Once a limit is exceeded,
+/// the least recently or least frequently used entries are evicted until
+/// the cache is back at 90% of the limit.
+///
+/// ## Example
+/// ```
+/// use gw2lib::{
+///     cache::{CacheLimits, Eviction, InMemoryCache},
+///     model::items::Item,
+/// };
+///
+/// let cache = InMemoryCache::default()
+///     .static_limits(CacheLimits::default().max_bytes(256 * 1024 * 1024))
+///     .authenticated_limits(CacheLimits::default().max_entries(10_000))
+///     .endpoint_limit::<Item>(50_000)
+///     .eviction(Eviction::Lfu);
+/// ```
 pub struct InMemoryCache {
-    statics: DashMap<(TypeId, u64), (NaiveDateTime, Box<dyn Any + Send + Sync>)>,
-    authenticated: DashMap<(TypeId, u64), (NaiveDateTime, Box<dyn Any + Send + Sync>)>,
+    statics: Partition,
+    authenticated: Partition,
+    eviction: Eviction,
+    clock: AtomicU64,
+}
+
+/// capacity limits for a part of an [`InMemoryCache`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CacheLimits {
+    max_entries: Option<usize>,
+    max_bytes: Option<usize>,
+}
+
+impl CacheLimits {
+    /// limits the number of entries
+    pub fn max_entries(self, max_entries: usize) -> Self {
+        Self {
+            max_entries: Some(max_entries),
+            ..self
+        }
+    }
+
+    /// limits the approximate size of all entries
+    ///
+    /// Entries are measured by the length of their json representation,
+    /// which costs a serialization per insert.
+    pub fn max_bytes(self, max_bytes: usize) -> Self {
+        Self {
+            max_bytes: Some(max_bytes),
+            ..self
+        }
+    }
+}
+
+/// which entries an [`InMemoryCache`] evicts first
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Eviction {
+    /// least recently used
+    #[default]
+    Lru,
+    /// least frequently used, ties are broken by recency
+    Lfu,
+}
+
+struct Partition {
+    map: DashMap<Key, Entry>,
+    limits: CacheLimits,
+    bytes: AtomicUsize,
+    endpoint_limits: HashMap<&'static str, usize>,
+    endpoint_counts: DashMap<&'static str, usize>,
+}
+
+struct Entry {
+    expiring: NaiveDateTime,
+    validators: Validators,
+    value: Box<dyn Any + Send + Sync>,
+    endpoint: &'static str,
+    /// hash of the identifier for authenticated entries
+    auth: Option<u64>,
+    size: usize,
+    last_used: AtomicU64,
+    uses: AtomicU64,
+}
+
+impl Entry {
+    /// whether the entry should still be kept at `now`
+    fn retain(&self, now: NaiveDateTime) -> bool {
+        now < self.expiring + stale_retention()
+    }
+
+    fn touch(&self, tick: u64) {
+        self.last_used.store(tick, Ordering::Relaxed);
+        self.uses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn score(&self, eviction: Eviction) -> (u64, u64) {
+        let last_used = self.last_used.load(Ordering::Relaxed);
+        match eviction {
+            Eviction::Lru => (last_used, 0),
+            Eviction::Lfu => (self.uses.load(Ordering::Relaxed), last_used),
+        }
+    }
 }
 
 impl Default for InMemoryCache {
     fn default() -> Self {
         let hasher = RandomState::new();
         Self {
-            statics: DashMap::with_hasher(hasher.clone()),
-            authenticated: DashMap::with_hasher(hasher),
+            statics: Partition::new(hasher.clone()),
+            authenticated: Partition::new(hasher),
+            eviction: Eviction::default(),
+            clock: AtomicU64::new(0),
         }
     }
 }
@@ -36,18 +143,12 @@ impl Cache for InMemoryCache {
         lang: Language,
         auth: &Option<A>,
     ) where
-        T: Clone + Send + Sync + 'static,
+        T: Serialize + Clone + Send + Sync + 'static,
         I: Hash + Sync + 'static + ?Sized,
         E: Endpoint,
         A: Hash + Sync + 'static,
     {
-        let hash = hash::<_, T, I, A>(self.statics.hasher(), id, E::LOCALE.then_some(lang), auth);
-        let map = if E::AUTHENTICATED {
-            &self.authenticated
-        } else {
-            &self.statics
-        };
-        map.insert(hash, (expiring, Box::new(endpoint.clone())));
+        self.insert_entry::<T, I, E, A>(id, endpoint, expiring, Validators::default(), lang, auth);
     }
 
     async fn get<T, I, E, A>(&self, id: &I, lang: Language, auth: &Option<A>) -> Option<T>
@@ -57,41 +158,319 @@ impl Cache for InMemoryCache {
         E: Endpoint,
         A: Hash + Sync + 'static,
     {
-        let hash = hash::<_, T, I, A>(self.statics.hasher(), id, E::LOCALE.then_some(lang), auth);
-        let map = if E::AUTHENTICATED {
-            &self.authenticated
-        } else {
-            &self.statics
-        };
-        let entry = map.entry(hash);
+        let hash = hash::<_, T, I, A>(self.hasher(), id, E::LOCALE.then_some(lang), auth);
+        let partition = self.partition::<E>();
+        let entry = partition.map.entry(hash);
         match entry {
-            Entry::Occupied(entry) => {
-                let (expiring, any) = entry.get();
+            MapEntry::Occupied(entry) => {
                 let now = Utc::now().naive_utc();
-                if now < *expiring {
-                    any.downcast_ref().cloned()
+                if now < entry.get().expiring {
+                    entry.get().touch(self.tick());
+                    entry.get().value.downcast_ref().cloned()
                 } else {
-                    entry.remove();
+                    if !entry.get().retain(now) {
+                        let (_, removed) = entry.remove_entry();
+                        partition.removed(&removed);
+                    }
                     None
                 }
             }
-            Entry::Vacant(_) => None,
+            MapEntry::Vacant(_) => None,
         }
     }
 
+    async fn insert_validated<T, I, E, A>(
+        &self,
+        id: &I,
+        endpoint: &T,
+        expiring: NaiveDateTime,
+        validators: &Validators,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: Serialize + Clone + Send + Sync + 'static,
+        I: Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Hash + Sync + 'static,
+    {
+        self.insert_entry::<T, I, E, A>(id, endpoint, expiring, validators.clone(), lang, auth);
+    }
+
+    async fn get_stale<T, I, E, A>(
+        &self,
+        id: &I,
+        lang: Language,
+        auth: &Option<A>,
+    ) -> Option<CacheEntry<T>>
+    where
+        T: Clone + Send + Sync + 'static,
+        I: Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Hash + Sync + 'static,
+    {
+        let hash = hash::<_, T, I, A>(self.hasher(), id, E::LOCALE.then_some(lang), auth);
+        let entry = self.partition::<E>().map.get(&hash)?;
+        entry.touch(self.tick());
+        Some(CacheEntry {
+            value: entry.value.downcast_ref::<T>()?.clone(),
+            expiring: entry.expiring,
+            validators: entry.validators.clone(),
+        })
+    }
+
     async fn cleanup(&self) {
         let now = Utc::now().naive_utc();
-        self.statics.retain(|_, (time, _)| *time < now);
-        self.authenticated.retain(|_, (time, _)| *time < now);
+        self.statics.retain(|entry| entry.retain(now));
+        self.authenticated.retain(|entry| entry.retain(now));
     }
 
     async fn wipe_static(&self) {
-        self.statics.clear();
+        self.statics.retain(|_| false);
     }
 
     async fn wipe_authenticated(&self) {
-        self.authenticated.clear();
+        self.authenticated.retain(|_| false);
+    }
+
+    async fn remove<T, I, E, A>(&self, id: &I, lang: Language, auth: &Option<A>)
+    where
+        T: Clone + Send + Sync + 'static,
+        I: Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Hash + Sync + 'static,
+    {
+        let hash = hash::<_, T, I, A>(self.hasher(), id, E::LOCALE.then_some(lang), auth);
+        let partition = self.partition::<E>();
+        if let Some((_, removed)) = partition.map.remove(&hash) {
+            partition.removed(&removed);
+        }
+    }
+
+    async fn wipe_endpoint<E: Endpoint>(&self) {
+        self.partition::<E>()
+            .retain(|entry| entry.endpoint != E::NAME);
+    }
+
+    async fn wipe_identifier<A>(&self, auth: &A)
+    where
+        A: Hash + Sync + 'static,
+    {
+        let auth = Some(self.hash_auth(auth));
+        self.authenticated.retain(|entry| entry.auth != auth);
+    }
+}
+
+impl InMemoryCache {
+    /// sets the limits for entries of unauthenticated endpoints
+    pub fn static_limits(mut self, limits: CacheLimits) -> Self {
+        self.statics.limits = limits;
+        self
     }
+
+    /// sets the limits for entries of authenticated endpoints
+    pub fn authenticated_limits(mut self, limits: CacheLimits) -> Self {
+        self.authenticated.limits = limits;
+        self
+    }
+
+    /// limits the number of entries of the endpoint `E`
+    ///
+    /// This includes all languages and, for authenticated endpoints, all
+    /// api keys.
+    pub fn endpoint_limit<E: Endpoint>(mut self, max_entries: usize) -> Self {
+        let partition = if E::AUTHENTICATED {
+            &mut self.authenticated
+        } else {
+            &mut self.statics
+        };
+        partition.endpoint_limits.insert(E::NAME, max_entries);
+        self
+    }
+
+    /// sets which entries are evicted first, defaults to [`Eviction::Lru`]
+    pub fn eviction(self, eviction: Eviction) -> Self {
+        Self { eviction, ..self }
+    }
+
+    /// the number of entries, including expired ones kept for revalidation
+    pub fn len(&self) -> usize {
+        self.statics.map.len() + self.authenticated.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn hasher(&self) -> &RandomState {
+        self.statics.map.hasher()
+    }
+
+    fn hash_auth<A: Hash + ?Sized>(&self, auth: &A) -> u64 {
+        self.hasher().hash_one(auth)
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn partition<E: Endpoint>(&self) -> &Partition {
+        if E::AUTHENTICATED {
+            &self.authenticated
+        } else {
+            &self.statics
+        }
+    }
+
+    fn insert_entry<T, I, E, A>(
+        &self,
+        id: &I,
+        endpoint: &T,
+        expiring: NaiveDateTime,
+        validators: Validators,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: Serialize + Clone + Send + Sync + 'static,
+        I: Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Hash + Sync + 'static,
+    {
+        let hash = hash::<_, T, I, A>(self.hasher(), id, E::LOCALE.then_some(lang), auth);
+        let partition = self.partition::<E>();
+        let size = if partition.limits.max_bytes.is_some() {
+            json_size(endpoint) + std::mem::size_of::<Entry>()
+        } else {
+            0
+        };
+        let entry = Entry {
+            expiring,
+            validators,
+            value: Box::new(endpoint.clone()),
+            endpoint: E::NAME,
+            auth: auth.as_ref().map(|auth| self.hash_auth(auth)),
+            size,
+            last_used: AtomicU64::new(self.tick()),
+            uses: AtomicU64::new(1),
+        };
+        partition.added(&entry);
+        if let Some(replaced) = partition.map.insert(hash, entry) {
+            partition.removed(&replaced);
+        }
+        partition.evict(self.eviction, E::NAME, &hash);
+    }
+}
+
+impl Partition {
+    fn new(hasher: RandomState) -> Self {
+        Self {
+            map: DashMap::with_hasher(hasher),
+            limits: CacheLimits::default(),
+            bytes: AtomicUsize::new(0),
+            endpoint_limits: HashMap::new(),
+            endpoint_counts: DashMap::new(),
+        }
+    }
+
+    fn added(&self, entry: &Entry) {
+        self.bytes.fetch_add(entry.size, Ordering::Relaxed);
+        if self.endpoint_limits.contains_key(entry.endpoint) {
+            *self.endpoint_counts.entry(entry.endpoint).or_default() += 1;
+        }
+    }
+
+    fn removed(&self, entry: &Entry) {
+        self.bytes.fetch_sub(entry.size, Ordering::Relaxed);
+        if let Some(mut count) = self.endpoint_counts.get_mut(entry.endpoint) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    fn retain(&self, mut f: impl FnMut(&Entry) -> bool) {
+        self.map.retain(|_, entry| {
+            let keep = f(entry);
+            if !keep {
+                self.removed(entry);
+            }
+            keep
+        });
+    }
+
+    /// evicts entries if the partition or `endpoint` exceed their limits,
+    /// never evicting `inserted`
+    fn evict(&self, eviction: Eviction, endpoint: &'static str, inserted: &Key) {
+        let excess_entries = excess(self.map.len(), self.limits.max_entries);
+        let excess_bytes = excess(self.bytes.load(Ordering::Relaxed), self.limits.max_bytes);
+        if excess_entries > 0 || excess_bytes > 0 {
+            self.evict_where(eviction, inserted, excess_entries, excess_bytes, |_| true);
+        }
+
+        if let Some(&max) = self.endpoint_limits.get(endpoint) {
+            let count = self.endpoint_counts.get(endpoint).map_or(0, |x| *x);
+            let excess_entries = excess(count, Some(max));
+            if excess_entries > 0 {
+                self.evict_where(eviction, inserted, excess_entries, 0, |x| {
+                    x.endpoint == endpoint
+                });
+            }
+        }
+    }
+
+    fn evict_where(
+        &self,
+        eviction: Eviction,
+        inserted: &Key,
+        mut excess_entries: usize,
+        mut excess_bytes: usize,
+        filter: impl Fn(&Entry) -> bool,
+    ) {
+        let mut candidates: Vec<_> = self
+            .map
+            .iter()
+            .filter(|x| x.key() != inserted && filter(x.value()))
+            .map(|x| (x.value().score(eviction), *x.key()))
+            .collect();
+        candidates.sort_unstable_by_key(|(score, _)| *score);
+
+        for (_, key) in candidates {
+            if excess_entries == 0 && excess_bytes == 0 {
+                break;
+            }
+            if let Some((_, entry)) = self.map.remove(&key) {
+                self.removed(&entry);
+                excess_entries = excess_entries.saturating_sub(1);
+                excess_bytes = excess_bytes.saturating_sub(entry.size);
+            }
+        }
+    }
+}
+
+/// how much `current` has to shrink to get back to 90% of `max`, if it
+/// exceeds `max`
+fn excess(current: usize, max: Option<usize>) -> usize {
+    match max {
+        Some(max) if current > max => current - (max - max / 10),
+        _ => 0,
+    }
+}
+
+/// the length of the json representation of `value`
+fn json_size<T: Serialize>(value: &T) -> usize {
+    struct Counter(usize);
+
+    impl io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    serde_json::to_writer(&mut counter, value).ok();
+    counter.0
 }
 
 #[inline]
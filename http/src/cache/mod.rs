@@ -1,19 +1,120 @@
 use std::{fmt::Display, hash::Hash, ops::Deref};
 
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
+use chrono::{Duration, NaiveDateTime};
 use gw2lib_model::{Endpoint, Language};
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+#[cfg(feature = "redis")]
+mod codec;
+#[cfg(feature = "disk")]
+mod disk;
 pub(crate) mod in_memory;
-pub use in_memory::InMemoryCache;
+#[cfg(feature = "disk")]
+pub use disk::DiskCache;
+pub use in_memory::{CacheLimits, Eviction, InMemoryCache};
 pub use noop::NoopCache;
+pub use policy::CachePolicy;
+pub use tiered::TieredCache;
 mod noop;
+mod policy;
 #[cfg(feature = "redis")]
 mod redis;
+mod tiered;
+
+#[cfg(feature = "deflate")]
+pub use codec::Deflate;
+#[cfg(feature = "msgpack")]
+pub use codec::MessagePack;
 
 #[cfg(feature = "redis")]
-pub use self::redis::RedisCache;
+pub use self::{
+    codec::{Codec, Compressed, Compression, Json},
+    redis::RedisCache,
+};
+
+/// validators of a response, used to ask the api whether it changed since
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validators {
+    /// the `etag` header
+    pub etag: Option<String>,
+    /// the `last-modified` header
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// true if the response had neither an `etag` nor a `last-modified`
+    /// header
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// a cached value together with its expiry and validators
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CacheEntry<T> {
+    pub value: T,
+    pub expiring: NaiveDateTime,
+    pub validators: Validators,
+}
+
+/// how long expired entries are kept for revalidation and [`CachePolicy`]
+pub(crate) fn stale_retention() -> Duration {
+    Duration::hours(1)
+}
+
+/// the default namespace of keys built by [`gen_key`]
+#[cfg(any(feature = "disk", feature = "redis"))]
+pub(crate) const KEY_PREFIX: &str = "gw2lib";
+
+/// builds the key of an entry for caches with string keys
+///
+/// Keys start with `{prefix}_static_` or `{prefix}_auth_{auth}_`, depending on
+/// `E::AUTHENTICATED`. The identifier, escaped by [`key_identifier`], directly
+/// follows `auth_`, so the entries of an identifier share a prefix.
+#[cfg(any(feature = "disk", feature = "redis"))]
+pub(crate) fn gen_key<E: Endpoint, I: Display + ?Sized, A: Display>(
+    prefix: &str,
+    id: &I,
+    lang: Language,
+    auth: &Option<A>,
+) -> String {
+    use std::fmt::Write;
+
+    let mut key = String::with_capacity(128);
+    let mut push = |s: &str| {
+        key.push_str(s);
+        key.push('_');
+    };
+
+    push(prefix);
+
+    if E::AUTHENTICATED {
+        push("auth");
+        push(&key_identifier(auth.as_ref().unwrap()));
+    } else {
+        push("static");
+    }
+
+    push(E::NAME);
+
+    if E::LOCALE {
+        push(lang.as_str());
+    }
+
+    write!(key, "{}", id).unwrap();
+
+    key
+}
+
+/// the identifier as written into keys by [`gen_key`]
+///
+/// `_` separates the parts of a key, so it gets escaped along with `%`. This
+/// keeps `{prefix}_auth_{auth}_` from being the prefix of another identifier.
+#[cfg(any(feature = "disk", feature = "redis"))]
+pub(crate) fn key_identifier<A: Display + ?Sized>(auth: &A) -> String {
+    auth.to_string().replace('%', "%25").replace('_', "%5F")
+}
 
 /// the interface for caching API responses
 /// ### Remarks
@@ -41,6 +142,96 @@ pub trait Cache {
         E: Endpoint,
         A: Display + Hash + Sync + 'static;
 
+    /// like [`Cache::insert`], additionally storing the validators of the
+    /// response
+    ///
+    /// The default implementation drops the validators.
+    async fn insert_validated<T, I, E, A>(
+        &self,
+        id: &I,
+        endpoint: &T,
+        expiring: NaiveDateTime,
+        validators: &Validators,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let _ = validators;
+        self.insert::<T, I, E, A>(id, endpoint, expiring, lang, auth)
+            .await
+    }
+
+    /// returns the entry even if it already expired
+    ///
+    /// Caches should keep entries for a while after they expired, so the
+    /// client can revalidate them with the api or serve them according to its
+    /// [`CachePolicy`].
+    /// The default implementation returns nothing, disabling both.
+    async fn get_stale<T, I, E, A>(
+        &self,
+        id: &I,
+        lang: Language,
+        auth: &Option<A>,
+    ) -> Option<CacheEntry<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let _ = (id, lang, auth);
+        None
+    }
+
+    /// looks up several entries of the same endpoint at once
+    ///
+    /// Returns the fresh values in the order of `ids`.
+    /// The default implementation calls [`Cache::get`] for every id.
+    async fn get_many<T, I, E, A>(
+        &self,
+        ids: &[I],
+        lang: Language,
+        auth: &Option<A>,
+    ) -> Vec<Option<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let mut values = Vec::with_capacity(ids.len());
+        for id in ids {
+            values.push(self.get::<T, I, E, A>(id, lang, auth).await);
+        }
+        values
+    }
+
+    /// stores several entries of the same endpoint, all expiring at the same
+    /// time
+    ///
+    /// The default implementation calls [`Cache::insert`] for every entry.
+    async fn insert_many<T, I, E, A>(
+        &self,
+        entries: &[(&I, &T)],
+        expiring: NaiveDateTime,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        for (id, endpoint) in entries {
+            self.insert::<T, I, E, A>(*id, *endpoint, expiring, lang, auth)
+                .await;
+        }
+    }
+
     async fn cleanup(&self);
 
     async fn wipe(&self) {
@@ -51,6 +242,46 @@ pub trait Cache {
     async fn wipe_static(&self);
 
     async fn wipe_authenticated(&self);
+
+    /// removes a single entry
+    ///
+    /// For localized endpoints, only the entry in `lang` is removed.
+    /// The default implementation wipes the whole endpoint.
+    async fn remove<T, I, E, A>(&self, id: &I, lang: Language, auth: &Option<A>)
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let _ = (id, lang, auth);
+        self.wipe_endpoint::<E>().await;
+    }
+
+    /// removes all entries of the endpoint `E`, in all languages and for all
+    /// identifiers
+    ///
+    /// The default implementation wipes all static or all authenticated
+    /// entries, depending on `E::AUTHENTICATED`.
+    async fn wipe_endpoint<E: Endpoint>(&self) {
+        if E::AUTHENTICATED {
+            self.wipe_authenticated().await;
+        } else {
+            self.wipe_static().await;
+        }
+    }
+
+    /// removes all entries cached for the identifier `auth`, e.g. after its
+    /// api key got revoked
+    ///
+    /// The default implementation wipes all authenticated entries.
+    async fn wipe_identifier<A>(&self, auth: &A)
+    where
+        A: Display + Hash + Sync + 'static,
+    {
+        let _ = auth;
+        self.wipe_authenticated().await;
+    }
 }
 
 #[async_trait]
@@ -102,6 +333,72 @@ where
         self.deref().get::<T, I, E, A>(id, lang, auth).await
     }
 
+    async fn insert_validated<T, I, E, A>(
+        &self,
+        id: &I,
+        endpoint: &T,
+        expiring: NaiveDateTime,
+        validators: &Validators,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        self.deref()
+            .insert_validated::<T, I, E, A>(id, endpoint, expiring, validators, lang, auth)
+            .await
+    }
+
+    async fn get_stale<T, I, E, A>(
+        &self,
+        id: &I,
+        lang: Language,
+        auth: &Option<A>,
+    ) -> Option<CacheEntry<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        self.deref().get_stale::<T, I, E, A>(id, lang, auth).await
+    }
+
+    async fn get_many<T, I, E, A>(
+        &self,
+        ids: &[I],
+        lang: Language,
+        auth: &Option<A>,
+    ) -> Vec<Option<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        self.deref().get_many::<T, I, E, A>(ids, lang, auth).await
+    }
+
+    async fn insert_many<T, I, E, A>(
+        &self,
+        entries: &[(&I, &T)],
+        expiring: NaiveDateTime,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        self.deref()
+            .insert_many::<T, I, E, A>(entries, expiring, lang, auth)
+            .await
+    }
+
     async fn cleanup(&self) {
         self.deref().cleanup().await
     }
@@ -113,4 +410,25 @@ where
     async fn wipe_authenticated(&self) {
         self.deref().wipe_authenticated().await
     }
+
+    async fn remove<T, I, E, A>(&self, id: &I, lang: Language, auth: &Option<A>)
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        self.deref().remove::<T, I, E, A>(id, lang, auth).await
+    }
+
+    async fn wipe_endpoint<E: Endpoint>(&self) {
+        self.deref().wipe_endpoint::<E>().await
+    }
+
+    async fn wipe_identifier<A>(&self, auth: &A)
+    where
+        A: Display + Hash + Sync + 'static,
+    {
+        self.deref().wipe_identifier(auth).await
+    }
 }
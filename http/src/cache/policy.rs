@@ -0,0 +1,67 @@
+use chrono::{Duration, Utc};
+
+use crate::cache::CacheEntry;
+
+/// decides when expired cache entries may still be returned
+///
+/// Caches keep expired entries for at most an hour, so longer durations
+/// behave like an hour.
+///
+/// ## Example
+/// ```no_run
+/// use chrono::Duration;
+/// use gw2lib::{cache::CachePolicy, Client};
+///
+/// let policy = CachePolicy::default()
+///     .stale_while_revalidate(Duration::minutes(1))
+///     .stale_if_error(Duration::minutes(30));
+/// let client = Client::default().cache_policy(policy);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CachePolicy {
+    stale_while_revalidate: Option<Duration>,
+    stale_if_error: Option<Duration>,
+}
+
+impl CachePolicy {
+    /// returns entries that expired less than `max_stale` ago immediately,
+    /// while refreshing them in the background
+    ///
+    /// Background refreshes share the inflight requests of the client, so
+    /// every entry is refreshed only once at a time.
+    pub fn stale_while_revalidate(self, max_stale: Duration) -> Self {
+        Self {
+            stale_while_revalidate: Some(max_stale),
+            ..self
+        }
+    }
+
+    /// returns entries that expired less than `max_stale` ago when the api
+    /// answers with a 5xx status or can't be reached
+    pub fn stale_if_error(self, max_stale: Duration) -> Self {
+        Self {
+            stale_if_error: Some(max_stale),
+            ..self
+        }
+    }
+
+    pub(crate) fn has_stale_while_revalidate(&self) -> bool {
+        self.stale_while_revalidate.is_some()
+    }
+
+    pub(crate) fn has_stale_if_error(&self) -> bool {
+        self.stale_if_error.is_some()
+    }
+
+    pub(crate) fn serve_while_revalidating<T>(&self, entry: &CacheEntry<T>) -> bool {
+        is_within(self.stale_while_revalidate, entry)
+    }
+
+    pub(crate) fn serve_on_error<T>(&self, entry: &CacheEntry<T>) -> bool {
+        is_within(self.stale_if_error, entry)
+    }
+}
+
+fn is_within<T>(max_stale: Option<Duration>, entry: &CacheEntry<T>) -> bool {
+    max_stale.is_some_and(|max_stale| Utc::now().naive_utc() < entry.expiring + max_stale)
+}
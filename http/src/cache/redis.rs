@@ -1,26 +1,54 @@
-use std::{
-    fmt::{Display, Write},
-    hash::Hash,
-};
+use std::{fmt::Display, hash::Hash};
 
 use async_trait::async_trait;
 use chrono::{NaiveDateTime, Utc};
 use gw2lib_model::{Endpoint, Language};
 use redis::{
-    aio::{Connection, ConnectionLike},
+    aio::{ConnectionLike, MultiplexedConnection},
     AsyncCommands, Client, Cmd, RedisError,
 };
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::cache::Cache;
+use crate::{
+    cache::{
+        gen_key, key_identifier, stale_retention, Cache, CacheEntry, Codec, Json, Validators,
+        KEY_PREFIX,
+    },
+    redis_connection::SharedConnection,
+};
 
+/// a cache shared by all clients connected to the same redis server
+///
+/// All clones share one multiplexed connection, which is opened on first use
+/// and reopened after it broke.
+///
+/// Keys start with a prefix, `gw2lib` unless set with
+/// [`RedisCache::prefix`], so several applications can share a redis
+/// instance without wiping each other's entries.
+/// Values are encoded by a [`Codec`], [`Json`] by default.
+///
+/// ## Example
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use gw2lib::{
+///     cache::{Json, RedisCache},
+///     Client,
+/// };
+///
+/// let redis = redis::Client::open("redis://localhost").unwrap();
+/// let cache = RedisCache::new(redis).prefix("myapp").codec(Json);
+/// let client = Client::default().cache(Arc::new(cache));
+/// ```
 #[derive(Debug, Clone)]
-pub struct RedisCache {
-    client: Client,
+pub struct RedisCache<C = Json> {
+    conn: SharedConnection,
+    prefix: String,
+    codec: C,
 }
 
 #[async_trait]
-impl Cache for RedisCache {
+impl<C: Codec> Cache for RedisCache<C> {
     async fn insert<T, I, E, A>(
         &self,
         id: &I,
@@ -34,17 +62,15 @@ impl Cache for RedisCache {
         E: Endpoint,
         A: Display + Hash + Sync + 'static,
     {
-        let mut conn = match self.connection().await {
-            Some(conn) => conn,
-            None => return,
-        };
-        let ex = expiring - Utc::now().naive_utc();
-        let key = self.gen_key::<E, I, A>(id, lang, auth);
-        if let Ok(value) = serde_json::to_string(endpoint) {
-            conn.set_ex::<_, _, ()>(key, value, ex.num_seconds().try_into().unwrap_or_default())
-                .await
-                .ok();
-        }
+        self.insert_validated::<T, I, E, A>(
+            id,
+            endpoint,
+            expiring,
+            &Validators::default(),
+            lang,
+            auth,
+        )
+        .await
     }
 
     async fn get<T, I, E, A>(&self, id: &I, lang: Language, auth: &Option<A>) -> Option<T>
@@ -54,57 +80,230 @@ impl Cache for RedisCache {
         E: Endpoint,
         A: Display + Hash + Sync + 'static,
     {
-        let mut conn = self.connection().await?;
-        let key = self.gen_key::<E, I, A>(id, lang, auth);
-        conn.get(key)
+        let entry = self.get_stale::<T, I, E, A>(id, lang, auth).await?;
+        (Utc::now().naive_utc() < entry.expiring).then_some(entry.value)
+    }
+
+    async fn insert_validated<T, I, E, A>(
+        &self,
+        id: &I,
+        endpoint: &T,
+        expiring: NaiveDateTime,
+        validators: &Validators,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let key = gen_key::<E, I, A>(&self.prefix, id, lang, auth);
+        let entry = CacheEntry {
+            value: endpoint,
+            expiring,
+            validators: validators.clone(),
+        };
+        let Some(value) = self.codec.encode(&entry) else {
+            return;
+        };
+        let ex = expiry_seconds(expiring);
+        self.conn
+            .run(|mut conn| async move { conn.set_ex::<_, _, ()>(key, value, ex).await })
+            .await
+            .ok();
+    }
+
+    async fn get_stale<T, I, E, A>(
+        &self,
+        id: &I,
+        lang: Language,
+        auth: &Option<A>,
+    ) -> Option<CacheEntry<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let key = gen_key::<E, I, A>(&self.prefix, id, lang, auth);
+        self.conn
+            .run(|mut conn| async move { conn.get(key).await })
             .await
             .ok()
-            .and_then(|x: String| serde_json::from_str(&x).ok())
+            .flatten()
+            .and_then(|x: Vec<u8>| self.codec.decode(&x))
+    }
+
+    async fn get_many<T, I, E, A>(
+        &self,
+        ids: &[I],
+        lang: Language,
+        auth: &Option<A>,
+    ) -> Vec<Option<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        if ids.is_empty() {
+            return Vec::new();
+        }
+        let keys: Vec<_> = ids
+            .iter()
+            .map(|id| gen_key::<E, I, A>(&self.prefix, id, lang, auth))
+            .collect();
+        let values: Vec<Option<Vec<u8>>> = self
+            .conn
+            .run(|mut conn| async move { conn.get(keys).await })
+            .await
+            .unwrap_or_default();
+        let now = Utc::now().naive_utc();
+        let mut values: Vec<_> = values
+            .into_iter()
+            .map(|x| {
+                x.and_then(|x| self.codec.decode::<CacheEntry<T>>(&x))
+                    .filter(|x| now < x.expiring)
+                    .map(|x| x.value)
+            })
+            .collect();
+        values.resize(ids.len(), None);
+        values
+    }
+
+    async fn insert_many<T, I, E, A>(
+        &self,
+        entries: &[(&I, &T)],
+        expiring: NaiveDateTime,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let ex = expiry_seconds(expiring);
+        let mut pipe = redis::pipe();
+        for (id, endpoint) in entries {
+            let entry = CacheEntry {
+                value: *endpoint,
+                expiring,
+                validators: Validators::default(),
+            };
+            if let Some(value) = self.codec.encode(&entry) {
+                pipe.set_ex(gen_key::<E, I, A>(&self.prefix, *id, lang, auth), value, ex)
+                    .ignore();
+            }
+        }
+        if pipe.cmd_iter().next().is_none() {
+            return;
+        }
+        self.conn
+            .run(|mut conn| async move { pipe.query_async::<_, ()>(&mut conn).await })
+            .await
+            .ok();
     }
 
     async fn cleanup(&self) {}
 
     async fn wipe_static(&self) {
-        self.delete_keys("gw2lib_static_*").await;
+        let pattern = format!("{}_static_*", escape_pattern(&self.prefix));
+        self.delete_keys(&pattern).await;
     }
 
     async fn wipe_authenticated(&self) {
-        self.delete_keys("gw2lib_auth_*").await;
+        let pattern = format!("{}_auth_*", escape_pattern(&self.prefix));
+        self.delete_keys(&pattern).await;
+    }
+
+    async fn remove<T, I, E, A>(&self, id: &I, lang: Language, auth: &Option<A>)
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let key = gen_key::<E, I, A>(&self.prefix, id, lang, auth);
+        self.conn
+            .run(|mut conn| async move { conn.del::<_, ()>(key).await })
+            .await
+            .ok();
+    }
+
+    async fn wipe_endpoint<E: Endpoint>(&self) {
+        // authenticated keys have the identifier in front of the endpoint
+        let kind = if E::AUTHENTICATED { "auth_*" } else { "static" };
+        let pattern = format!(
+            "{}_{kind}_{}_*",
+            escape_pattern(&self.prefix),
+            escape_pattern(E::NAME)
+        );
+        self.delete_keys(&pattern).await;
+    }
+
+    async fn wipe_identifier<A>(&self, auth: &A)
+    where
+        A: Display + Hash + Sync + 'static,
+    {
+        let pattern = format!(
+            "{}_auth_{}_*",
+            escape_pattern(&self.prefix),
+            escape_pattern(&key_identifier(auth))
+        );
+        self.delete_keys(&pattern).await;
     }
 }
 
 impl RedisCache {
     pub fn new(client: Client) -> Self {
-        Self { client }
+        Self {
+            conn: SharedConnection::new(client),
+            prefix: KEY_PREFIX.to_string(),
+            codec: Json,
+        }
     }
 }
 
-impl RedisCache {
-    async fn connection(&self) -> Option<Connection> {
-        self.client.get_async_connection().await.ok()
+impl<C> RedisCache<C> {
+    /// sets the namespace all keys start with, `gw2lib` by default
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// sets how values are encoded, [`Json`] by default
+    pub fn codec<D: Codec>(self, codec: D) -> RedisCache<D> {
+        RedisCache {
+            conn: self.conn,
+            prefix: self.prefix,
+            codec,
+        }
     }
 
     async fn delete_keys(&self, pattern: &str) {
-        let mut conn = match self.connection().await {
-            Some(conn) => conn,
-            None => return,
-        };
         let cmd = redis::cmd("SCAN").arg("MATCH").arg(pattern).clone();
         let mut cursor = 0;
 
         loop {
-            Self::delete_keys_from_cursor(&mut conn, cmd.clone(), &mut cursor)
-                .await
-                .ok();
+            let res = self
+                .conn
+                .run(|mut conn| {
+                    let cmd = cmd.clone();
+                    let cursor = &mut cursor;
+                    async move { Self::delete_keys_from_cursor(&mut conn, cmd, cursor).await }
+                })
+                .await;
 
-            if cursor == 0 {
+            if res.is_err() || cursor == 0 {
                 break;
             }
         }
     }
 
     async fn delete_keys_from_cursor(
-        conn: &mut Connection,
+        conn: &mut MultiplexedConnection,
         mut cmd: Cmd,
         cursor: &mut u64,
     ) -> Result<(), RedisError> {
@@ -120,41 +319,28 @@ impl RedisCache {
         };
         *cursor = cur;
 
+        if chunk.is_empty() {
+            return Ok(());
+        }
         conn.del::<_, ()>(chunk).await
     }
+}
 
-    fn gen_key<E: Endpoint, I: Display + ?Sized, A: Display>(
-        &self,
-        id: &I,
-        lang: Language,
-        auth: &Option<A>,
-    ) -> String {
-        let mut key = String::with_capacity(128);
-        let mut push = |s: &str| {
-            key.push_str(s);
-            key.push('_');
-        };
-
-        push("gw2lib");
-
-        if E::AUTHENTICATED {
-            push("auth");
-        } else {
-            push("static");
-        }
-
-        push(E::URL);
-
-        if E::LOCALE {
-            push(lang.as_str());
-        }
+/// seconds until the entry can be dropped, keeping it around for
+/// revalidation and stale policies
+fn expiry_seconds(expiring: NaiveDateTime) -> usize {
+    let ex = expiring - Utc::now().naive_utc() + stale_retention();
+    ex.num_seconds().try_into().unwrap_or_default()
+}
 
-        if E::AUTHENTICATED {
-            write!(key, "{}_", auth.as_ref().unwrap()).unwrap();
+/// escapes the glob characters of `SCAN MATCH` patterns
+fn escape_pattern(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
         }
-
-        write!(key, "{}", id).unwrap();
-
-        key
+        escaped.push(c);
     }
+    escaped
 }
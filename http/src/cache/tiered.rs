@@ -0,0 +1,262 @@
+use std::{fmt::Display, hash::Hash};
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use gw2lib_model::{Endpoint, Language};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::cache::{Cache, CacheEntry, Validators};
+
+/// two caches layered on top of each other
+///
+/// Reads check the local `L1` first and fall back to the shared `L2`,
+/// copying hits from `L2` into `L1`.
+/// Writes, [`Cache::cleanup`] and the `wipe_*` functions go to both layers.
+///
+/// `L2` has to implement [`Cache::get_stale`], as promoting an entry needs its
+/// expiry. A typical `L2` is a `RedisCache` shared by many workers.
+///
+/// ## Example
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use gw2lib::{
+///     cache::{InMemoryCache, TieredCache},
+///     Client,
+/// };
+///
+/// let shared = Arc::new(InMemoryCache::default());
+/// let cache = TieredCache::new(InMemoryCache::default(), shared.clone());
+/// let client = Client::empty().cache(Arc::new(cache));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TieredCache<L1, L2> {
+    l1: L1,
+    l2: L2,
+}
+
+impl<L1, L2> TieredCache<L1, L2> {
+    pub fn new(l1: L1, l2: L2) -> Self {
+        Self { l1, l2 }
+    }
+
+    /// the local layer
+    pub fn l1(&self) -> &L1 {
+        &self.l1
+    }
+
+    /// the shared layer
+    pub fn l2(&self) -> &L2 {
+        &self.l2
+    }
+}
+
+#[async_trait]
+impl<L1, L2> Cache for TieredCache<L1, L2>
+where
+    L1: Cache + Send + Sync,
+    L2: Cache + Send + Sync,
+{
+    async fn insert<T, I, E, A>(
+        &self,
+        id: &I,
+        endpoint: &T,
+        expiring: NaiveDateTime,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        self.l1
+            .insert::<T, I, E, A>(id, endpoint, expiring, lang, auth)
+            .await;
+        self.l2
+            .insert::<T, I, E, A>(id, endpoint, expiring, lang, auth)
+            .await;
+    }
+
+    async fn get<T, I, E, A>(&self, id: &I, lang: Language, auth: &Option<A>) -> Option<T>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        if let Some(value) = self.l1.get::<T, I, E, A>(id, lang, auth).await {
+            return Some(value);
+        }
+        let entry = self.promote::<T, I, E, A>(id, lang, auth).await?;
+        (Utc::now().naive_utc() < entry.expiring).then_some(entry.value)
+    }
+
+    async fn insert_validated<T, I, E, A>(
+        &self,
+        id: &I,
+        endpoint: &T,
+        expiring: NaiveDateTime,
+        validators: &Validators,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        self.l1
+            .insert_validated::<T, I, E, A>(id, endpoint, expiring, validators, lang, auth)
+            .await;
+        self.l2
+            .insert_validated::<T, I, E, A>(id, endpoint, expiring, validators, lang, auth)
+            .await;
+    }
+
+    async fn get_stale<T, I, E, A>(
+        &self,
+        id: &I,
+        lang: Language,
+        auth: &Option<A>,
+    ) -> Option<CacheEntry<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let local = self.l1.get_stale::<T, I, E, A>(id, lang, auth).await;
+        if local
+            .as_ref()
+            .is_some_and(|x| Utc::now().naive_utc() < x.expiring)
+        {
+            return local;
+        }
+        // another client might have refreshed the entry in the shared layer
+        match self.promote::<T, I, E, A>(id, lang, auth).await {
+            Some(shared) if local.as_ref().is_none_or(|x| x.expiring < shared.expiring) => {
+                Some(shared)
+            }
+            _ => local,
+        }
+    }
+
+    async fn get_many<T, I, E, A>(
+        &self,
+        ids: &[I],
+        lang: Language,
+        auth: &Option<A>,
+    ) -> Vec<Option<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let mut values = self.l1.get_many::<T, I, E, A>(ids, lang, auth).await;
+        let now = Utc::now().naive_utc();
+        for (id, value) in ids.iter().zip(&mut values) {
+            if value.is_none() {
+                *value = self
+                    .promote::<T, I, E, A>(id, lang, auth)
+                    .await
+                    .filter(|x| now < x.expiring)
+                    .map(|x| x.value);
+            }
+        }
+        values
+    }
+
+    async fn insert_many<T, I, E, A>(
+        &self,
+        entries: &[(&I, &T)],
+        expiring: NaiveDateTime,
+        lang: Language,
+        auth: &Option<A>,
+    ) where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        self.l1
+            .insert_many::<T, I, E, A>(entries, expiring, lang, auth)
+            .await;
+        self.l2
+            .insert_many::<T, I, E, A>(entries, expiring, lang, auth)
+            .await;
+    }
+
+    async fn cleanup(&self) {
+        self.l1.cleanup().await;
+        self.l2.cleanup().await;
+    }
+
+    async fn wipe_static(&self) {
+        self.l1.wipe_static().await;
+        self.l2.wipe_static().await;
+    }
+
+    async fn wipe_authenticated(&self) {
+        self.l1.wipe_authenticated().await;
+        self.l2.wipe_authenticated().await;
+    }
+
+    async fn remove<T, I, E, A>(&self, id: &I, lang: Language, auth: &Option<A>)
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        self.l1.remove::<T, I, E, A>(id, lang, auth).await;
+        self.l2.remove::<T, I, E, A>(id, lang, auth).await;
+    }
+
+    async fn wipe_endpoint<E: Endpoint>(&self) {
+        self.l1.wipe_endpoint::<E>().await;
+        self.l2.wipe_endpoint::<E>().await;
+    }
+
+    async fn wipe_identifier<A>(&self, auth: &A)
+    where
+        A: Display + Hash + Sync + 'static,
+    {
+        self.l1.wipe_identifier(auth).await;
+        self.l2.wipe_identifier(auth).await;
+    }
+}
+
+impl<L1, L2> TieredCache<L1, L2>
+where
+    L1: Cache + Send + Sync,
+    L2: Cache + Send + Sync,
+{
+    /// reads the entry from `L2`, copying it into `L1`
+    async fn promote<T, I, E, A>(
+        &self,
+        id: &I,
+        lang: Language,
+        auth: &Option<A>,
+    ) -> Option<CacheEntry<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        I: Display + Hash + Sync + 'static + ?Sized,
+        E: Endpoint,
+        A: Display + Hash + Sync + 'static,
+    {
+        let entry = self.l2.get_stale::<T, I, E, A>(id, lang, auth).await?;
+        self.l1
+            .insert_validated::<T, I, E, A>(
+                id,
+                &entry.value,
+                entry.expiring,
+                &entry.validators,
+                lang,
+                auth,
+            )
+            .await;
+        Some(entry)
+    }
+}
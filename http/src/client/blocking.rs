@@ -1,11 +1,20 @@
-use std::{fmt::Display, hash::Hash};
+use std::{collections::HashMap, fmt::Display, hash::Hash};
 
 use chrono::Duration;
-use gw2lib_model::{BulkEndpoint, Endpoint, EndpointWithId, FixedEndpoint};
+use futures::{stream::BoxStream, StreamExt};
+use gw2lib_model::{
+    BulkEndpoint, Endpoint, EndpointWithId, FixedEndpoint, Language, PagedEndpoint,
+    ParentBulkEndpoint, ParentEndpoint, QueryEndpoint,
+};
 use serde::{de::DeserializeOwned, Serialize};
+use tokio::runtime::Runtime;
 
 use super::requester::Requester as Req;
-use crate::{block::block, CachedRequest, Client, EndpointResult};
+use crate::{
+    block::{block, runtime},
+    cache::CachePolicy,
+    CachedRequest, Client, EndpointResult, ManyResult, Page, Priority,
+};
 
 pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>:
     Req<AUTHENTICATED, FORCE>
@@ -34,11 +43,108 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>:
     fn cached(
         &self,
         cache_duration: Duration,
-    ) -> CachedRequest<Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
     {
         Req::cached(self, cache_duration)
     }
 
+    /// overwrites the cache policy for all requests returned from this
+    /// function
+    /// ## Example
+    /// ```
+    /// use chrono::Duration;
+    /// use gw2lib::{cache::CachePolicy, Client, Requester};
+    /// use gw2lib::model::tradingpost::Prices;
+    ///
+    /// let client = Client::default();
+    /// let policy = CachePolicy::default().stale_if_error(Duration::minutes(10));
+    /// // these requests return expired prices if the api is down
+    /// let prices: Vec<Prices> = client.with_policy(policy).many(vec![19721]).unwrap();
+    fn with_policy(
+        &self,
+        cache_policy: CachePolicy,
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
+    {
+        Req::with_policy(self, cache_policy)
+    }
+
+    /// sets the priority of all requests returned from this function
+    ///
+    /// Requests waiting for the rate limiter are sent by priority. Every
+    /// identifier waits on its own, so a throttled identifier does not hold
+    /// up the others.
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{
+    ///     model::{authenticated::account::Account, items::Item},
+    ///     Client, Priority, Requester,
+    /// };
+    ///
+    /// let client = Client::default().api_key("<api key>");
+    /// // the crawl does not delay the account request
+    /// let crawler = client.clone();
+    /// std::thread::spawn(move || {
+    ///     let _: Vec<Item> = crawler.priority(Priority::Low).all().unwrap();
+    /// });
+    /// let account: Account = client.priority(Priority::High).get().unwrap();
+    /// ```
+    fn priority(
+        &self,
+        priority: Priority,
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
+    {
+        Req::priority(self, priority)
+    }
+
+    /// limits how long each request of the returned requester may take
+    ///
+    /// Includes waiting for the rate limiter, all retries and waiting for an
+    /// identical request already in flight. Requests taking longer fail with
+    /// [`crate::EndpointError::Timeout`].
+    ///
+    /// The deadline applies to every request a call sends on its own, so
+    /// calls split into several requests, like [`Self::many`] with more than
+    /// 200 ids, can take longer in total.
+    /// ## Example
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use gw2lib::{model::misc::build::Build, Client, EndpointError, Requester};
+    ///
+    /// let client = Client::default();
+    /// let build: Result<Build, _> = client.deadline(Duration::from_secs(2)).get();
+    /// if let Err(EndpointError::Timeout) = build {
+    ///     println!("the api is slow");
+    /// }
+    /// ```
+    fn deadline(
+        &self,
+        deadline: std::time::Duration,
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
+    {
+        Req::deadline(self, deadline)
+    }
+
+    /// requests everything returned from this function in `lang` instead of
+    /// the language of the client
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{
+    ///     model::{items::Item, Language},
+    ///     Client, Requester,
+    /// };
+    ///
+    /// let client = Client::default();
+    /// let item: Item = client.lang(Language::De).single(19721).unwrap();
+    /// ```
+    fn lang(
+        &self,
+        lang: Language,
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
+    {
+        Req::lang(self, lang)
+    }
+
     /// forces a fresh copy from the api
     /// ## Example
     /// ```
@@ -53,7 +159,7 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>:
     /// let build_id: Build = client.forced().get().unwrap();
     fn forced(
         &self,
-    ) -> CachedRequest<Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, true>
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, true>
     {
         Req::forced(self)
     }
@@ -65,6 +171,65 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>:
         block(Req::get(self))
     }
 
+    /// call an endpoint with query parameters
+    ///
+    /// responses are cached per set of parameters
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{
+    ///     model::tradingpost::{CoinsToGems, ExchangeQuery},
+    ///     Client, Requester,
+    /// };
+    ///
+    /// let client = Client::default();
+    /// let query = ExchangeQuery { quantity: 100_000 };
+    /// let gems: CoinsToGems = client.query(&query).unwrap();
+    /// ```
+    fn query<T: DeserializeOwned + Serialize + Clone + Send + Sync + QueryEndpoint + 'static>(
+        &self,
+        params: &T::Query,
+    ) -> EndpointResult<T> {
+        block(Req::query(self, params))
+    }
+
+    /// call an endpoint that has no model yet, like `v2/wizardsvault`
+    ///
+    /// Goes through the rate limiter, error mapping and cache like any other
+    /// request, responses are cached per path and query. Rate limits and
+    /// metrics are tracked per path. The api key of authenticated clients is
+    /// sent along. Requests the latest schema version.
+    ///
+    /// `path` has to be url encoded, `query` is anything serializable into
+    /// query parameters, e.g. `&[("ids", "1,2")]`. The language is not sent
+    /// unless `query` contains `lang`, e.g. `&[("lang", "de")]`.
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{Client, Requester};
+    ///
+    /// let client = Client::default();
+    /// let vault = client.raw("v2/wizardsvault", &[("lang", "de")]).unwrap();
+    /// println!("{}", vault["title"]);
+    /// ```
+    fn raw<Q: Serialize + Sync + ?Sized>(
+        &self,
+        path: &str,
+        query: &Q,
+    ) -> EndpointResult<serde_json::Value> {
+        block(Req::raw(self, path, query))
+    }
+
+    /// like [`Self::raw`], but parses the response into `T`
+    fn raw_as<
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        Q: Serialize + Sync + ?Sized,
+    >(
+        &self,
+        path: &str,
+        query: &Q,
+    ) -> EndpointResult<T> {
+        block(Req::raw_as(self, path, query))
+    }
+
     /// request a single item
     fn single<
         T: DeserializeOwned + Serialize + Clone + Send + Sync + EndpointWithId<IdType = I> + 'static,
@@ -76,6 +241,31 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>:
         block(Req::single(self, id))
     }
 
+    /// requests a single item in every language at once
+    ///
+    /// Every language is cached on its own. Endpoints without localized
+    /// content answer with the same item for every language.
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{
+    ///     model::{items::Item, Language},
+    ///     Client, Requester,
+    /// };
+    ///
+    /// let client = Client::default();
+    /// let names = client.localized::<Item, _>(19721).unwrap();
+    /// println!("{}", names[&Language::Fr].name);
+    /// ```
+    fn localized<
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + EndpointWithId<IdType = I> + 'static,
+        I: Display + DeserializeOwned + Hash + Send + Sync + Clone + 'static,
+    >(
+        &self,
+        id: I,
+    ) -> EndpointResult<HashMap<Language, T>> {
+        block(Req::localized(self, id))
+    }
+
     /// retrieves an item from cache
     /// ```
     /// use gw2lib::{model::items::Item, Client, Requester};
@@ -104,6 +294,9 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>:
     }
 
     /// request multiple ids at once
+    ///
+    /// ids the api does not know are left out, use [`Self::many_detailed`]
+    /// to find out which
     fn many<
         T: DeserializeOwned
             + Serialize
@@ -121,24 +314,74 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>:
         block(Req::many(self, ids))
     }
 
-    /// requests a page of items and returns the number of total items across
-    /// all pages
-    fn page<
+    /// request multiple ids at once, reporting the ids the api does not know
+    ///
+    /// Unknown ids are remembered for the duration set with
+    /// [`Client::cache_not_found`], so they don't get requested again in the
+    /// meantime.
+    fn many_detailed<
         T: DeserializeOwned
+            + Serialize
             + EndpointWithId<IdType = I>
             + BulkEndpoint
             + Clone
             + Send
             + Sync
             + 'static,
-        I: Display + DeserializeOwned + Hash + Clone + Sync + 'static,
+        I: Display + DeserializeOwned + Hash + Clone + Eq + Send + Sync + 'static,
     >(
+        &self,
+        ids: Vec<I>,
+    ) -> EndpointResult<ManyResult<T, I>> {
+        block(Req::many_detailed(self, ids))
+    }
+
+    /// request multiple ids at once, yielding entries as soon as their chunk
+    /// arrives
+    ///
+    /// Entries from cache come first, followed by the requested chunks in the
+    /// order they complete and finally entries that were already being
+    /// requested elsewhere.
+    /// A failed chunk yields a single error and the iterator continues with
+    /// the remaining chunks.
+    fn stream_many<
+        'a,
+        T: DeserializeOwned
+            + Serialize
+            + EndpointWithId<IdType = I>
+            + BulkEndpoint
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        I: Display + DeserializeOwned + Hash + Clone + Eq + Send + Sync + 'static,
+    >(
+        &'a self,
+        ids: Vec<I>,
+    ) -> BlockingStream<'a, T> {
+        BlockingStream::new(Req::stream_many(self, ids))
+    }
+
+    /// requests a page of items
+    ///
+    /// pages are not cached
+    fn page<T: DeserializeOwned + PagedEndpoint + Clone + Send + Sync + 'static>(
         &self,
         page: usize,
         page_size: u8,
-        result: &mut Vec<T>,
-    ) -> EndpointResult<usize> {
-        block(Req::page(self, page, page_size, result))
+    ) -> EndpointResult<Page<T>> {
+        block(Req::page(self, page, page_size))
+    }
+
+    /// requests every page, yielding them in order
+    ///
+    /// Up to four pages after the first one are requested at the same time,
+    /// going through the rate limiter like any other request.
+    fn pages<'a, T: DeserializeOwned + PagedEndpoint + Clone + Send + Sync + 'static>(
+        &'a self,
+        page_size: u8,
+    ) -> BlockingStream<'a, Page<T>> {
+        BlockingStream::new(Req::pages(self, page_size))
     }
 
     /// requests all items using the most efficient method available
@@ -176,7 +419,7 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>:
             + Send
             + Sync
             + 'static,
-        I: Display + DeserializeOwned + Hash + Clone + Sync + 'static,
+        I: Display + DeserializeOwned + Hash + Clone + Eq + Send + Sync + 'static,
     >(
         &self,
     ) -> EndpointResult<Vec<T>> {
@@ -186,25 +429,37 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>:
     /// Gets all items by querying all pages
     ///
     /// use [`Self::all`] to use the most efficient way to request all items
-    fn get_all_by_paging<
+    fn get_all_by_paging<T: DeserializeOwned + PagedEndpoint + Clone + Send + Sync + 'static>(
+        &self,
+    ) -> EndpointResult<Vec<T>> {
+        block(Req::get_all_by_paging(self))
+    }
+
+    /// Gets all items by querying all ids
+    ///
+    /// use [`Self::all`] to use the most efficient way to request all items
+    fn get_all_by_requesting_ids<
         T: DeserializeOwned
+            + Serialize
             + EndpointWithId<IdType = I>
             + BulkEndpoint
             + Clone
             + Send
             + Sync
             + 'static,
-        I: Display + DeserializeOwned + Hash + Clone + Sync + 'static,
+        I: Display + DeserializeOwned + Serialize + Hash + Clone + Send + Sync + Eq + 'static,
     >(
         &self,
     ) -> EndpointResult<Vec<T>> {
-        block(Req::get_all_by_paging(self))
+        block(Req::get_all_by_requesting_ids(self))
     }
 
-    /// Gets all items by querying all ids
+    /// requests all items, yielding entries as soon as their chunk arrives
     ///
-    /// use [`Self::all`] to use the most efficient way to request all items
-    fn get_all_by_requesting_ids<
+    /// uses the same method as [`Self::all`], so endpoints supporting
+    /// `ids=all` yield everything at once after a single request
+    fn stream_all<
+        'a,
         T: DeserializeOwned
             + Serialize
             + EndpointWithId<IdType = I>
@@ -214,10 +469,150 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>:
             + Sync
             + 'static,
         I: Display + DeserializeOwned + Serialize + Hash + Clone + Send + Sync + Eq + 'static,
+    >(
+        &'a self,
+    ) -> BlockingStream<'a, T> {
+        BlockingStream::new(Req::stream_all(self))
+    }
+
+    /// request a resource below a parent, like the members of a guild
+    ///
+    /// responses are cached per parent
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{model::guild::members::GuildMembers, Client, Requester};
+    ///
+    /// let client = Client::default().api_key("<api key>");
+    /// let members: GuildMembers = client.scoped("<guild id>".to_string()).unwrap();
+    /// ```
+    fn scoped<
+        T: DeserializeOwned + Serialize + ParentEndpoint<ParentId = P> + Clone + Send + Sync + 'static,
+        P: Display + Send,
     >(
         &self,
+        parent: P,
+    ) -> EndpointResult<T> {
+        block(Req::scoped(self, parent))
+    }
+
+    /// request all ids available below a parent
+    fn scoped_ids<
+        T: DeserializeOwned
+            + Serialize
+            + ParentBulkEndpoint<ParentId = P, IdType = I>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        P: Display + Send,
+        I: Display + DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+    >(
+        &self,
+        parent: P,
+    ) -> EndpointResult<Vec<I>> {
+        block(Req::scoped_ids::<T, P, I>(self, parent))
+    }
+
+    /// request a single item below a parent
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{model::authenticated::characters::BuildTab, Client, Requester};
+    ///
+    /// let client = Client::default().api_key("<api key>");
+    /// let tab: BuildTab = client.scoped_single("My Character".to_string(), 1).unwrap();
+    /// ```
+    fn scoped_single<
+        T: DeserializeOwned
+            + Serialize
+            + ParentBulkEndpoint<ParentId = P, IdType = I>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        P: Display + Send,
+        I: Display + Send,
+    >(
+        &self,
+        parent: P,
+        id: I,
+    ) -> EndpointResult<T> {
+        block(Req::scoped_single(self, parent, id))
+    }
+
+    /// request multiple items below a parent at once
+    ///
+    /// Works like [`Self::many`]: every item is cached on its own and only
+    /// the ones missing from cache get requested. Items come back in the
+    /// order of `ids`, ids the api does not know are left out.
+    fn scoped_many<
+        T: DeserializeOwned
+            + Serialize
+            + ParentBulkEndpoint<ParentId = P, IdType = I>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        P: Display + Send,
+        I: Display + Send,
+    >(
+        &self,
+        parent: P,
+        ids: Vec<I>,
     ) -> EndpointResult<Vec<T>> {
-        block(Req::get_all_by_requesting_ids(self))
+        block(Req::scoped_many(self, parent, ids))
+    }
+
+    /// request all items below a parent
+    ///
+    /// uses `ids=all` if the endpoint supports it, otherwise
+    /// [`Self::scoped_ids`] followed by [`Self::scoped_many`]
+    fn scoped_all<
+        T: DeserializeOwned
+            + Serialize
+            + ParentBulkEndpoint<ParentId = P, IdType = I>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        P: Display + Clone + Send + Sync,
+        I: Display + DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+    >(
+        &self,
+        parent: P,
+    ) -> EndpointResult<Vec<T>> {
+        block(Req::scoped_all::<T, P, I>(self, parent))
+    }
+}
+
+/// iterator over the entries of [`Requester::stream_many`] and
+/// [`Requester::stream_all`], or the pages of [`Requester::pages`]
+///
+/// Requests only make progress while [`Iterator::next`] is being called.
+#[must_use]
+pub struct BlockingStream<'a, T> {
+    // dropped before the runtime driving it
+    stream: BoxStream<'a, EndpointResult<T>>,
+    runtime: Runtime,
+}
+
+impl<'a, T> BlockingStream<'a, T> {
+    fn new(stream: BoxStream<'a, EndpointResult<T>>) -> Self {
+        Self {
+            stream,
+            runtime: runtime(),
+        }
+    }
+}
+
+impl<T> Iterator for BlockingStream<'_, T> {
+    type Item = EndpointResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
     }
 }
 
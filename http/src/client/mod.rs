@@ -1,28 +1,39 @@
 mod requester;
 use core::default::Default;
+#[cfg(feature = "testing")]
+use std::path::PathBuf;
 use std::{
     any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
     sync::{Arc, Weak},
 };
 
 #[cfg(feature = "blocking")]
-pub use blocking::Requester;
+pub use blocking::{BlockingStream, Requester};
 #[cfg(not(feature = "blocking"))]
 pub use requester::Requester;
 #[cfg(feature = "blocking")]
 mod blocking;
 
-use chrono::Duration;
+use chrono::{Duration, Utc};
 use dashmap::DashMap;
-use gw2lib_model::Language;
+use gw2lib_model::{misc::build::Build, Language};
 use hyper::client::{connect::Connect, HttpConnector};
 use hyper_rustls::HttpsConnector;
 use static_init::dynamic;
 use tokio::sync::Mutex;
 
+#[cfg(feature = "metrics")]
+use crate::metrics::{ClientStats, Metrics};
+#[cfg(feature = "testing")]
+use crate::testing::Cassette;
 use crate::{
-    cache::{CleanupCache, InMemoryCache},
-    BucketRateLimiter, Cache, NoopCache, NoopRateLimiter, RateLimiter,
+    cache::{CachePolicy, CleanupCache, InMemoryCache},
+    redact,
+    retry::RetryPolicy,
+    scheduler::{Priority, Scheduler},
+    BucketRateLimiter, Cache, EndpointResult, NoopCache, NoopRateLimiter, RateLimiter,
 };
 
 pub(crate) type Inflight = Arc<DashMap<(TypeId, u64), Box<dyn Any + Send + Sync>>>;
@@ -41,18 +52,29 @@ pub struct Client<
     identifier: Option<String>,
     cache: Arc<C>,
     inflight: Inflight,
-    rate_limiter: R,
+    rate_limiter: Arc<R>,
+    scheduler: Arc<Scheduler>,
+    retry: RetryPolicy,
+    timeout: Option<std::time::Duration>,
+    connect_timeout: Option<std::time::Duration>,
+    cache_policy: CachePolicy,
+    not_found_duration: Duration,
+    #[cfg(feature = "testing")]
+    cassette: Option<Arc<Cassette>>,
+    #[cfg(feature = "metrics")]
+    metrics: Arc<Metrics>,
 }
 
 impl Client<NoopCache, NoopRateLimiter, HttpsConnector<HttpConnector>, false> {
     /// creates a new gw2 api client
     /// ### Warning
     /// this is not the same as [`Client::default`]!
-    /// This initializes a client without any caching or rate limiting.
+    /// This initializes a client without any caching, rate limiting,
+    /// retries or timeouts.
     /// If you want to use a default cache and rate limiter, use
     /// [`Client::default`].
     pub fn empty() -> Self {
-        let client = create_client();
+        let client = create_client(None);
         let rate_limiter = NoopRateLimiter {};
         Self {
             host: "https://api.guildwars2.com".to_string(),
@@ -62,14 +84,24 @@ impl Client<NoopCache, NoopRateLimiter, HttpsConnector<HttpConnector>, false> {
             identifier: None,
             cache: Arc::new(NoopCache {}),
             inflight: Default::default(),
-            rate_limiter,
+            rate_limiter: Arc::new(rate_limiter),
+            scheduler: Default::default(),
+            retry: RetryPolicy::disabled(),
+            timeout: None,
+            connect_timeout: None,
+            cache_policy: CachePolicy::default(),
+            not_found_duration: Duration::zero(),
+            #[cfg(feature = "testing")]
+            cassette: None,
+            #[cfg(feature = "metrics")]
+            metrics: Default::default(),
         }
     }
 }
 
 impl Default for Client<InMemoryCache, BucketRateLimiter, HttpsConnector<HttpConnector>, false> {
     fn default() -> Self {
-        let client = create_client();
+        let client = create_client(None);
         let rate_limiter = BucketRateLimiter::default();
         let cache = Arc::new(InMemoryCache::default());
         periodically_cleanup_cache(cache.clone());
@@ -81,7 +113,17 @@ impl Default for Client<InMemoryCache, BucketRateLimiter, HttpsConnector<HttpCon
             identifier: None,
             cache,
             inflight: Default::default(),
-            rate_limiter,
+            rate_limiter: Arc::new(rate_limiter),
+            scheduler: Default::default(),
+            retry: RetryPolicy::default(),
+            timeout: Some(std::time::Duration::from_secs(30)),
+            connect_timeout: None,
+            cache_policy: CachePolicy::default(),
+            not_found_duration: Duration::minutes(5),
+            #[cfg(feature = "testing")]
+            cassette: None,
+            #[cfg(feature = "metrics")]
+            metrics: Default::default(),
         }
     }
 }
@@ -102,7 +144,7 @@ impl<
         self,
         host: impl Into<String>,
     ) -> Client<C, R, HttpsConnector<HttpConnector>, AUTHENTICATED> {
-        let client = create_client();
+        let client = create_client(self.connect_timeout);
         Client {
             host: host.into(),
             language: self.language,
@@ -112,6 +154,16 @@ impl<
             cache: self.cache,
             inflight: self.inflight,
             rate_limiter: self.rate_limiter,
+            scheduler: self.scheduler,
+            retry: self.retry,
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            cache_policy: self.cache_policy,
+            not_found_duration: self.not_found_duration,
+            #[cfg(feature = "testing")]
+            cassette: self.cassette,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
         }
     }
 
@@ -119,7 +171,7 @@ impl<
     ///
     /// for https hosts use [`Client::host`]
     pub fn host_http(self, host: impl Into<String>) -> Client<C, R, HttpConnector, AUTHENTICATED> {
-        let client = hyper::Client::new();
+        let client = create_http_client(self.connect_timeout);
         Client {
             host: host.into(),
             language: self.language,
@@ -129,6 +181,44 @@ impl<
             cache: self.cache,
             inflight: self.inflight,
             rate_limiter: self.rate_limiter,
+            scheduler: self.scheduler,
+            retry: self.retry,
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            cache_policy: self.cache_policy,
+            not_found_duration: self.not_found_duration,
+            #[cfg(feature = "testing")]
+            cassette: self.cassette,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+        }
+    }
+
+    /// records every response into a new cassette file at `path`
+    ///
+    /// see [`Cassette`] for details
+    #[cfg(feature = "testing")]
+    pub fn record(self, path: impl Into<PathBuf>) -> Self {
+        self.cassette(Arc::new(Cassette::record(path)))
+    }
+
+    /// serves all responses from the cassette file at `path` instead of the
+    /// network
+    ///
+    /// see [`Cassette`] for details
+    #[cfg(feature = "testing")]
+    pub fn replay(self, path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        Ok(self.cassette(Arc::new(Cassette::replay(path)?)))
+    }
+
+    /// sets the cassette to record into or replay from
+    ///
+    /// Use this to share one cassette between multiple clients.
+    #[cfg(feature = "testing")]
+    pub fn cassette(self, cassette: Arc<Cassette>) -> Self {
+        Client {
+            cassette: Some(cassette),
+            ..self
         }
     }
 
@@ -140,6 +230,84 @@ impl<
         }
     }
 
+    /// sets the policy for retrying failed requests
+    ///
+    /// default is [`RetryPolicy::default`]
+    pub fn retry(self, retry: RetryPolicy) -> Self {
+        Client { retry, ..self }
+    }
+
+    /// sets how long a single attempt may take to connect and receive the
+    /// whole response, `None` disables the timeout
+    ///
+    /// Attempts taking longer fail with [`crate::EndpointError::Timeout`] and
+    /// get retried according to the [`RetryPolicy`]. To limit the time of a
+    /// request including the rate limiter and all retries, use
+    /// [`Requester::deadline`].
+    ///
+    /// default is 30 seconds
+    pub fn timeout(self, timeout: impl Into<Option<std::time::Duration>>) -> Self {
+        Client {
+            timeout: timeout.into(),
+            ..self
+        }
+    }
+
+    /// sets when expired cache entries may still be returned
+    ///
+    /// default is to never return expired entries
+    pub fn cache_policy(self, cache_policy: CachePolicy) -> Self {
+        Client {
+            cache_policy,
+            ..self
+        }
+    }
+
+    /// sets how long ids the api does not know about are remembered
+    ///
+    /// Bulk requests skip remembered ids and single requests fail with
+    /// [`crate::ApiError::NotFound`] without asking the api again.
+    /// Default is 5 minutes, zero disables it.
+    pub fn cache_not_found(self, not_found_duration: Duration) -> Self {
+        Client {
+            not_found_duration,
+            ..self
+        }
+    }
+
+    /// wipes all static cache entries whenever the game build changes
+    ///
+    /// Polls `v2/build` every `interval` in the background, until all clients
+    /// sharing this cache are dropped. The last seen build is stored in the
+    /// cache itself, so persistent or shared caches notice patches released
+    /// while no client was running.
+    ///
+    /// Static data rarely changes outside of patches, so this allows caching
+    /// static endpoints for much longer with [`Requester::cached`].
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use gw2lib::{model::items::Item, Client, Requester};
+    ///
+    /// # async fn run() -> Result<(), gw2lib::EndpointError> {
+    /// let client = Client::default().wipe_on_new_build(Duration::from_secs(300));
+    /// # #[cfg(not(feature = "blocking"))]
+    /// let item: Item = client
+    ///     .cached(chrono::Duration::days(7))
+    ///     .single(19721_u32)
+    ///     .await?;
+    /// # #[cfg(feature = "blocking")]
+    /// # let item: Item = client.cached(chrono::Duration::days(7)).single(19721_u32)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wipe_on_new_build(self, interval: std::time::Duration) -> Self {
+        watch_build(self.clone(), interval);
+        self
+    }
+
     /// sets a new api key
     pub fn api_key(self, key: impl Into<String>) -> Client<C, R, Conn, true> {
         let key = key.into();
@@ -152,6 +320,16 @@ impl<
             cache: self.cache,
             inflight: self.inflight,
             rate_limiter: self.rate_limiter,
+            scheduler: self.scheduler,
+            retry: self.retry,
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            cache_policy: self.cache_policy,
+            not_found_duration: self.not_found_duration,
+            #[cfg(feature = "testing")]
+            cassette: self.cassette,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
         }
     }
 
@@ -211,6 +389,16 @@ impl<
             cache,
             inflight: self.inflight,
             rate_limiter: self.rate_limiter,
+            scheduler: self.scheduler,
+            retry: self.retry,
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            cache_policy: self.cache_policy,
+            not_found_duration: self.not_found_duration,
+            #[cfg(feature = "testing")]
+            cassette: self.cassette,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
         }
     }
 
@@ -238,7 +426,61 @@ impl<
             identifier: self.identifier,
             cache: self.cache,
             inflight: self.inflight,
-            rate_limiter,
+            rate_limiter: Arc::new(rate_limiter),
+            scheduler: Default::default(),
+            retry: self.retry,
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            cache_policy: self.cache_policy,
+            not_found_duration: self.not_found_duration,
+            #[cfg(feature = "testing")]
+            cassette: self.cassette,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+        }
+    }
+}
+
+impl<
+        C: Cache + Send + Sync + 'static,
+        R: RateLimiter + Send + Sync + 'static,
+        const AUTHENTICATED: bool,
+    > Client<C, R, HttpsConnector<HttpConnector>, AUTHENTICATED>
+{
+    /// sets how long connecting to the host may take, `None` waits as long
+    /// as the operating system does
+    ///
+    /// Counts towards [`Client::timeout`], so it only helps when set lower.
+    ///
+    /// default is `None`
+    pub fn connect_timeout(self, connect_timeout: impl Into<Option<std::time::Duration>>) -> Self {
+        let connect_timeout = connect_timeout.into();
+        Client {
+            client: create_client(connect_timeout),
+            connect_timeout,
+            ..self
+        }
+    }
+}
+
+impl<
+        C: Cache + Send + Sync + 'static,
+        R: RateLimiter + Send + Sync + 'static,
+        const AUTHENTICATED: bool,
+    > Client<C, R, HttpConnector, AUTHENTICATED>
+{
+    /// sets how long connecting to the host may take, `None` waits as long
+    /// as the operating system does
+    ///
+    /// Counts towards [`Client::timeout`], so it only helps when set lower.
+    ///
+    /// default is `None`
+    pub fn connect_timeout(self, connect_timeout: impl Into<Option<std::time::Duration>>) -> Self {
+        let connect_timeout = connect_timeout.into();
+        Client {
+            client: create_http_client(connect_timeout),
+            connect_timeout,
+            ..self
         }
     }
 }
@@ -261,11 +503,63 @@ impl<
     fn cache_duration(&self) -> Duration {
         Duration::zero()
     }
+
+    fn policy(&self) -> &CachePolicy {
+        &self.cache_policy
+    }
+
+    fn priority_class(&self) -> Priority {
+        Priority::Normal
+    }
+
+    fn call_deadline(&self) -> Option<std::time::Duration> {
+        None
+    }
+
+    fn request_language(&self) -> Language {
+        self.language
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl<
+        C: Cache + Send + Sync + 'static,
+        R: RateLimiter + Send + Sync + 'static,
+        Conn: Connect + Clone + Send + Sync + 'static,
+        const AUTHENTICATED: bool,
+    > Client<C, R, Conn, AUTHENTICATED>
+{
+    /// takes a snapshot of the cache and request counters
+    ///
+    /// All clients derived from the same client share their counters.
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{model::items::Item, Client, Requester};
+    ///
+    /// # async fn run() -> Result<(), gw2lib::EndpointError> {
+    /// let client = Client::default();
+    /// # #[cfg(not(feature = "blocking"))]
+    /// let _: Vec<Item> = client.many(vec![19721_u32, 19993]).await?;
+    /// # #[cfg(feature = "blocking")]
+    /// # let _: Vec<Item> = client.many(vec![19721_u32, 19993])?;
+    /// let stats = client.stats();
+    /// let items = &stats.endpoints["v2/items"];
+    /// println!("{} hits, {} misses", items.cache_hits, items.cache_misses);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats(&self) -> ClientStats {
+        self.metrics.snapshot()
+    }
+
+    pub(crate) fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
 }
 
 impl<
         C: Cache + Send + Sync + 'static,
-        R: RateLimiter + Clone + Send + Sync + 'static,
+        R: RateLimiter + Send + Sync + 'static,
         Conn: Connect + Clone + Send + Sync + 'static,
         const AUTHENTICATED: bool,
     > Clone for Client<C, R, Conn, AUTHENTICATED>
@@ -280,10 +574,81 @@ impl<
             cache: self.cache.clone(),
             inflight: self.inflight.clone(),
             rate_limiter: self.rate_limiter.clone(),
+            scheduler: self.scheduler.clone(),
+            retry: self.retry.clone(),
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            cache_policy: self.cache_policy.clone(),
+            not_found_duration: self.not_found_duration,
+            #[cfg(feature = "testing")]
+            cassette: self.cassette.clone(),
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics.clone(),
         }
     }
 }
 
+/// never prints the api key, nor the identifier while it is the api key
+impl<
+        C: Cache + Send + Sync + 'static,
+        R: RateLimiter + Send + Sync + 'static,
+        Conn: Connect + Clone + Send + Sync + 'static,
+        const AUTHENTICATED: bool,
+    > fmt::Debug for Client<C, R, Conn, AUTHENTICATED>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = self.api_key.as_deref();
+        f.debug_struct("Client")
+            .field("host", &self.host)
+            .field("language", &self.language)
+            .field("api_key", &key.map(|_| "<redacted>"))
+            .field(
+                "identifier",
+                &self.identifier.as_deref().map(|x| redact(x, key)),
+            )
+            .field("retry", &self.retry)
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("cache_policy", &self.cache_policy)
+            .field("not_found_duration", &self.not_found_duration)
+            .finish_non_exhaustive()
+    }
+}
+
+/// a page of a [`crate::model::PagedEndpoint`], see [`Requester::page`]
+#[derive(Clone, Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// the index of this page, starting at 0
+    pub page: usize,
+    /// `x-page-total`, the number of pages
+    pub page_total: usize,
+    /// `x-page-size`
+    pub page_size: usize,
+    /// `x-result-count`, the number of items on this page
+    pub result_count: usize,
+    /// `x-result-total`, the number of items across all pages
+    pub result_total: usize,
+    /// the urls of the `Link` header by relation, e.g. `next` or `last`
+    pub links: HashMap<String, String>,
+}
+
+impl<T> Page<T> {
+    /// whether there are no pages after this one
+    pub fn is_last(&self) -> bool {
+        self.page + 1 >= self.page_total
+    }
+}
+
+/// the outcome of [`Requester::many_detailed`]
+#[derive(Clone, Debug)]
+pub struct ManyResult<T, I> {
+    /// entries returned by the api or the cache
+    pub found: Vec<T>,
+    /// ids the api did not return
+    pub missing: Vec<I>,
+}
+
 #[must_use]
 pub struct CachedRequest<
     'client,
@@ -295,6 +660,10 @@ pub struct CachedRequest<
 > {
     client: &'client Client<C, R, Conn, AUTHENTICATED>,
     cache_duration: Duration,
+    cache_policy: CachePolicy,
+    priority: Priority,
+    deadline: Option<std::time::Duration>,
+    language: Language,
 }
 
 impl<
@@ -317,17 +686,102 @@ impl<
     fn cache_duration(&self) -> Duration {
         self.cache_duration
     }
+
+    fn policy(&self) -> &CachePolicy {
+        &self.cache_policy
+    }
+
+    fn priority_class(&self) -> Priority {
+        self.priority
+    }
+
+    fn call_deadline(&self) -> Option<std::time::Duration> {
+        self.deadline
+    }
+
+    fn request_language(&self) -> Language {
+        self.language
+    }
 }
 
-fn create_client() -> hyper::Client<HttpsConnector<HttpConnector>, hyper::Body> {
+fn create_client(
+    connect_timeout: Option<std::time::Duration>,
+) -> hyper::Client<HttpsConnector<HttpConnector>, hyper::Body> {
+    let mut http = HttpConnector::new();
+    http.enforce_http(false);
+    http.set_connect_timeout(connect_timeout);
     let https = hyper_rustls::HttpsConnectorBuilder::new()
         .with_native_roots()
         .https_only()
         .enable_http1()
-        .build();
+        .wrap_connector(http);
     hyper::Client::builder().build(https)
 }
 
+fn create_http_client(
+    connect_timeout: Option<std::time::Duration>,
+) -> hyper::Client<HttpConnector, hyper::Body> {
+    let mut http = HttpConnector::new();
+    http.set_connect_timeout(connect_timeout);
+    hyper::Client::builder().build(http)
+}
+
+/// the cache id the last seen [`Build`] is stored under
+const LAST_BUILD: &str = "last_build";
+
+fn watch_build<
+    C: Cache + Send + Sync + 'static,
+    R: RateLimiter + Send + Sync + 'static,
+    Conn: Connect + Clone + Send + Sync + 'static,
+    const AUTHENTICATED: bool,
+>(
+    client: Client<C, R, Conn, AUTHENTICATED>,
+    interval: std::time::Duration,
+) {
+    let task = async move {
+        // the clone in this task is the last one using the cache
+        while Arc::strong_count(&client.cache) > 1 {
+            let _res = check_build(&client).await;
+            #[cfg(feature = "tracing")]
+            if let Err(e) = _res {
+                tracing::warn!(error = %e, "failed to check the game build");
+            }
+            tokio::time::sleep(interval).await;
+        }
+    };
+
+    crate::block::spawn(task);
+}
+
+async fn check_build<
+    C: Cache + Send + Sync + 'static,
+    R: RateLimiter + Send + Sync + 'static,
+    Conn: Connect + Clone + Send + Sync + 'static,
+    const AUTHENTICATED: bool,
+>(
+    client: &Client<C, R, Conn, AUTHENTICATED>,
+) -> EndpointResult<()> {
+    let build: Build = requester::Requester::get(&requester::Requester::forced(client)).await?;
+    let cache = &client.cache;
+    let last = cache
+        .get::<Build, str, Build, String>(LAST_BUILD, Language::En, &None)
+        .await
+        .map(|x| x.id);
+    if last == Some(build.id) {
+        return Ok(());
+    }
+    if last.is_some() {
+        #[cfg(feature = "tracing")]
+        tracing::info!(build = build.id, "new game build, wiping static cache");
+        cache.wipe_static().await;
+    }
+    let expiring = Utc::now().naive_utc() + Duration::days(365);
+    cache
+        .insert::<Build, str, Build, String>(LAST_BUILD, &build, expiring, Language::En, &None)
+        .await;
+    Ok(())
+}
+
 fn periodically_cleanup_cache(cache: Arc<dyn CleanupCache + Send + Sync + 'static>) {
     #[dynamic]
     static CACHES: Mutex<Vec<Weak<dyn CleanupCache + Send + Sync>>> =
@@ -1,7 +1,11 @@
 use std::{
     any::TypeId,
+    borrow::Cow,
+    collections::HashMap,
     fmt::Display,
+    future::Future,
     hash::Hash,
+    marker::PhantomData,
     ops::Deref,
     str::FromStr,
     sync::{Arc, Weak},
@@ -9,14 +13,24 @@ use std::{
 
 use async_trait::async_trait;
 use chrono::{Duration, NaiveDateTime, Utc};
-use dashmap::{mapref::entry::Entry, DashMap};
+use dashmap::mapref::entry::Entry;
 use either::Either;
-use futures::{stream::FuturesUnordered, StreamExt};
+use futures::{
+    future,
+    stream::{self, BoxStream, FuturesUnordered},
+    StreamExt,
+};
 use gw2lib_model::{
     BulkEndpoint, Endpoint, EndpointWithId, ErrorResponse, FixedEndpoint, Language, PagedEndpoint,
+    ParentBulkEndpoint, ParentEndpoint, QueryEndpoint,
+};
+use hyper::{
+    body::Bytes,
+    client::connect::Connect,
+    header::{HeaderValue, AUTHORIZATION, IF_MODIFIED_SINCE, IF_NONE_MATCH},
+    Request, Response, StatusCode, Uri,
 };
-use hyper::{body::Buf, client::connect::Connect, Request, Response, Uri};
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio::sync::{
     broadcast::{self, Receiver, Sender},
     Mutex,
@@ -25,8 +39,12 @@ use tokio::sync::{
 use tracing::{instrument, Instrument};
 
 use crate::{
-    cache::in_memory::hash, ApiError, Cache, CachedRequest, Client, EndpointError, EndpointResult,
-    Inflight, RateLimiter,
+    cache::{in_memory::hash, CacheEntry, CachePolicy, Validators},
+    rate_limit::RequestContext,
+    redact,
+    scheduler::Priority,
+    ApiError, Cache, CachedRequest, Client, EndpointError, EndpointResult, ErrorContext, Inflight,
+    ManyResult, Page, RateLimiter,
 };
 
 #[async_trait]
@@ -42,6 +60,18 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
     #[doc(hidden)]
     fn cache_duration(&self) -> Duration;
 
+    #[doc(hidden)]
+    fn policy(&self) -> &CachePolicy;
+
+    #[doc(hidden)]
+    fn priority_class(&self) -> Priority;
+
+    #[doc(hidden)]
+    fn call_deadline(&self) -> Option<std::time::Duration>;
+
+    #[doc(hidden)]
+    fn request_language(&self) -> Language;
+
     /// overwrites the cache duration for all requests returned from this
     /// function ## Example
     /// ```
@@ -60,11 +90,93 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
     fn cached(
         &self,
         cache_duration: Duration,
-    ) -> CachedRequest<Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
     {
         CachedRequest {
             client: self.client(),
             cache_duration,
+            cache_policy: self.policy().clone(),
+            priority: self.priority_class(),
+            deadline: self.call_deadline(),
+            language: self.request_language(),
+        }
+    }
+
+    /// overwrites the cache policy for all requests returned from this
+    /// function
+    fn with_policy(
+        &self,
+        cache_policy: CachePolicy,
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
+    {
+        CachedRequest {
+            client: self.client(),
+            cache_duration: self.cache_duration(),
+            cache_policy,
+            priority: self.priority_class(),
+            deadline: self.call_deadline(),
+            language: self.request_language(),
+        }
+    }
+
+    /// sets the priority of all requests returned from this function
+    ///
+    /// Requests waiting for the rate limiter are sent by priority. Every
+    /// identifier waits on its own, so a throttled identifier does not hold
+    /// up the others.
+    fn priority(
+        &self,
+        priority: Priority,
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
+    {
+        CachedRequest {
+            client: self.client(),
+            cache_duration: self.cache_duration(),
+            cache_policy: self.policy().clone(),
+            priority,
+            deadline: self.call_deadline(),
+            language: self.request_language(),
+        }
+    }
+
+    /// limits how long each request of the returned requester may take
+    ///
+    /// Includes waiting for the rate limiter, all retries and waiting for an
+    /// identical request already in flight. Requests taking longer fail with
+    /// [`EndpointError::Timeout`].
+    ///
+    /// The deadline applies to every request a call sends on its own, so
+    /// calls split into several requests, like [`Self::many`] with more than
+    /// 200 ids, can take longer in total.
+    fn deadline(
+        &self,
+        deadline: std::time::Duration,
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
+    {
+        CachedRequest {
+            client: self.client(),
+            cache_duration: self.cache_duration(),
+            cache_policy: self.policy().clone(),
+            priority: self.priority_class(),
+            deadline: Some(deadline),
+            language: self.request_language(),
+        }
+    }
+
+    /// requests everything returned from this function in `lang` instead of
+    /// the language of the client
+    fn lang(
+        &self,
+        lang: Language,
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, FORCE>
+    {
+        CachedRequest {
+            client: self.client(),
+            cache_duration: self.cache_duration(),
+            cache_policy: self.policy().clone(),
+            priority: self.priority_class(),
+            deadline: self.call_deadline(),
+            language: lang,
         }
     }
 
@@ -82,11 +194,15 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
     /// let build_id: Build = client.forced().get().unwrap();
     fn forced(
         &self,
-    ) -> CachedRequest<Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, true>
+    ) -> CachedRequest<'_, Self::Caching, Self::RateLimiting, Self::Connector, AUTHENTICATED, true>
     {
         CachedRequest {
             client: self.client(),
             cache_duration: Duration::zero(),
+            cache_policy: self.policy().clone(),
+            priority: self.priority_class(),
+            deadline: self.call_deadline(),
+            language: self.request_language(),
         }
     }
 
@@ -97,7 +213,92 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
     >(
         &self,
     ) -> EndpointResult<T> {
-        get_or_ids::<T, T, Self, AUTHENTICATED, FORCE>(self).await
+        get_or_ids::<T, T, Self, AUTHENTICATED, FORCE>(self, T::NAME, "", T::URL, "").await
+    }
+
+    /// call an endpoint with query parameters
+    ///
+    /// responses are cached per set of parameters
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{
+    ///     model::tradingpost::{CoinsToGems, ExchangeQuery},
+    ///     Client, Requester,
+    /// };
+    ///
+    /// # async fn run() -> Result<(), gw2lib::EndpointError> {
+    /// let client = Client::default();
+    /// let query = ExchangeQuery { quantity: 100_000 };
+    /// # #[cfg(not(feature = "blocking"))]
+    /// let gems: CoinsToGems = client.query(&query).await?;
+    /// # #[cfg(feature = "blocking")]
+    /// # let gems: CoinsToGems = client.query(&query)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", instrument(skip_all, fields(endpoint = %T::URL)))]
+    async fn query<
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + QueryEndpoint + 'static,
+    >(
+        &self,
+        params: &T::Query,
+    ) -> EndpointResult<T> {
+        let query = serde_urlencoded::to_string(params)?;
+        get_or_ids::<T, T, Self, AUTHENTICATED, FORCE>(self, T::NAME, &query, T::URL, &query).await
+    }
+
+    /// call an endpoint that has no model yet, like `v2/wizardsvault`
+    ///
+    /// Goes through the rate limiter, error mapping and cache like any other
+    /// request, responses are cached per path and query. Rate limits and
+    /// metrics are tracked per path. The api key of authenticated clients is
+    /// sent along. Requests the latest schema version.
+    ///
+    /// `path` has to be url encoded, `query` is anything serializable into
+    /// query parameters, e.g. `&[("ids", "1,2")]`. The language is not sent
+    /// unless `query` contains `lang`, e.g. `&[("lang", "de")]`.
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{Client, Requester};
+    ///
+    /// # async fn run() -> Result<(), gw2lib::EndpointError> {
+    /// let client = Client::default();
+    /// # #[cfg(not(feature = "blocking"))]
+    /// let vault = client.raw("v2/wizardsvault", &[("lang", "de")]).await?;
+    /// # #[cfg(feature = "blocking")]
+    /// # let vault = client.raw("v2/wizardsvault", &[("lang", "de")])?;
+    /// println!("{}", vault["title"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", instrument(skip_all, fields(path)))]
+    async fn raw<Q: Serialize + Sync + ?Sized>(
+        &self,
+        path: &str,
+        query: &Q,
+    ) -> EndpointResult<serde_json::Value> {
+        self.raw_as(path, query).await
+    }
+
+    /// like [`Self::raw`], but parses the response into `T`
+    #[cfg_attr(feature = "tracing", instrument(skip_all, fields(path)))]
+    async fn raw_as<
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+        Q: Serialize + Sync + ?Sized,
+    >(
+        &self,
+        path: &str,
+        query: &Q,
+    ) -> EndpointResult<T> {
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("path", path);
+        let path = path.trim_start_matches('/');
+        let query = serde_urlencoded::to_string(query)?;
+        let key = format!("{path}?{query}");
+        get_or_ids::<Raw<AUTHENTICATED>, T, Self, AUTHENTICATED, FORCE>(
+            self, path, &key, path, &query,
+        )
+        .await
     }
 
     /// request a single item
@@ -112,10 +313,23 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
         let id = id.into();
         #[cfg(feature = "tracing")]
         tracing::Span::current().record("id", id.to_string());
-        let lang = self.client().language;
-        if let Some(c) = self.try_get(&id).await {
+        let lang = self.request_language();
+        let cached = self.try_get(&id).await;
+        record_lookup::<Self, AUTHENTICATED, FORCE>(self, T::NAME, cached.is_some());
+        if let Some(c) = cached {
             return Ok(c);
         }
+        if check_missing::<T, I, Self, AUTHENTICATED, FORCE>(self, &id).await {
+            return Err(EndpointError::ApiError(
+                ApiError::NotFound,
+                not_found_context(&T::format_url(&T::format_id(&id)), &id.to_string()),
+            ));
+        }
+        let stale = check_stale::<T, I, T, Self, AUTHENTICATED, FORCE>(self, &id).await;
+        if let Some(stale) = stale.filter(|x| self.policy().serve_while_revalidating(x)) {
+            refresh_single::<T, I, Self, AUTHENTICATED, FORCE>(self, id);
+            return Ok(stale.value);
+        }
 
         let tx = loop {
             let either = check_inflight::<T, I, T, String>(
@@ -126,7 +340,11 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
             )
             .await;
             match either {
-                Some(Either::Left(mut rx)) => return rx.recv().await.map_err(Into::into),
+                Some(Either::Left(mut rx)) => {
+                    record_inflight_join::<Self, AUTHENTICATED, FORCE>(self, T::NAME);
+                    let received = with_deadline(self, async { Ok(rx.recv().await?) }).await?;
+                    return received.map_err(Into::into);
+                }
                 Some(Either::Right(tx)) => break tx,
                 None => {
                     if let Some(c) = self.try_get(&id).await {
@@ -136,20 +354,64 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
             }
         };
 
-        let request = build_request::<T, String, Self, AUTHENTICATED, FORCE>(
-            self,
-            T::format_url(T::format_id(&id).as_ref()),
-            None,
-        )?;
+        let stale = check_stale::<T, I, T, Self, AUTHENTICATED, FORCE>(self, &id).await;
+        let result = async {
+            let mut request = build_request::<T, String, Self, AUTHENTICATED, FORCE>(
+                self,
+                T::format_url(T::format_id(&id).as_ref()),
+                None,
+            )?;
+            add_validators(&mut request, stale.as_ref());
+
+            let response = exec_req::<Self, AUTHENTICATED, FORCE>(self, request).await?;
+            if response.status() == StatusCode::NOT_FOUND {
+                cache_missing::<T, I, Self, AUTHENTICATED, FORCE>(self, &id).await;
+                return Err(EndpointError::ApiError(
+                    ApiError::NotFound,
+                    error_context(&response),
+                ));
+            }
+            cache_response_or_revalidate::<I, T, T, Self, AUTHENTICATED, FORCE>(
+                self,
+                &id,
+                response,
+                stale.as_ref(),
+            )
+            .await
+        }
+        .await;
+        let result = serve_stale_on_error(self.policy(), result, stale);
+        if let Some(inflight) = to_inflight(&result) {
+            // ignoring the error is fine here
+            // the receiving side will check the cache if nothing got sent
+            let _ = tx.lock().await.send(inflight);
+        }
 
-        let response = exec_req::<Self, AUTHENTICATED, FORCE>(self, request).await?;
-        let result =
-            cache_response::<I, T, T, Self, AUTHENTICATED, FORCE>(self, &id, response).await?;
-        // ignoring the error is fine here
-        // the receiving side will check the cache if nothing got sent
-        let _ = tx.lock().await.send(result.clone());
+        result
+    }
 
-        Ok(result)
+    /// requests a single item in every language at once
+    ///
+    /// Every language is cached on its own. Endpoints without localized
+    /// content answer with the same item for every language.
+    async fn localized<
+        T: DeserializeOwned + Serialize + Clone + Send + Sync + EndpointWithId<IdType = I> + 'static,
+        I: Display + DeserializeOwned + Hash + Send + Sync + Clone + 'static,
+    >(
+        &self,
+        id: impl Into<I> + Send,
+    ) -> EndpointResult<HashMap<Language, T>> {
+        let id = id.into();
+        let requests = Language::ALL.map(|lang| {
+            let id = id.clone();
+            async move {
+                let item = self.lang(lang).single::<T, I>(id).await?;
+                Ok((lang, item))
+            }
+        });
+        future::try_join_all(requests)
+            .await
+            .map(|x| x.into_iter().collect())
     }
 
     /// retrieves an item from cache
@@ -181,10 +443,13 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
     >(
         &self,
     ) -> EndpointResult<Vec<I>> {
-        get_or_ids::<T, Vec<I>, Self, AUTHENTICATED, FORCE>(self).await
+        get_or_ids::<T, Vec<I>, Self, AUTHENTICATED, FORCE>(self, T::NAME, "", T::URL, "").await
     }
 
     /// request multiple ids at once
+    ///
+    /// ids the api does not know are left out, use [`Self::many_detailed`]
+    /// to find out which
     #[cfg_attr(feature = "tracing", instrument(skip_all, fields(endpoint = %T::URL)))]
     async fn many<
         T: DeserializeOwned
@@ -200,121 +465,148 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
         &self,
         ids: Vec<impl Into<I> + Send>,
     ) -> EndpointResult<Vec<T>> {
-        let mut result = Vec::with_capacity(ids.len());
-        let ids = if !FORCE {
-            let ids = extract_many_from_cache(self, ids, &mut result).await;
-            if ids.is_empty() {
-                return Ok(result);
-            }
-            ids
-        } else {
-            ids.into_iter().map(|id| id.into()).collect()
-        };
-
-        let txs = DashMap::with_capacity(ids.len());
-        let mut rxs = Vec::with_capacity(ids.len());
-        let mut remaining_ids = Vec::with_capacity(ids.len());
-        for id in ids {
-            let retain = loop {
-                let either = check_inflight::<T, I, T, String>(
-                    &self.client().inflight,
-                    &id,
-                    self.client().language,
-                    &self.client().identifier,
-                )
-                .await;
-                match either {
-                    Some(Either::Left(rx)) => {
-                        rxs.push(rx);
-                        break false;
-                    }
-                    Some(Either::Right(tx)) => {
-                        txs.insert(id.clone(), tx);
-                        break true;
-                    }
-                    None => {
-                        if let Some(c) =
-                            check_cache::<T, I, T, Self, AUTHENTICATED, FORCE>(self, &id).await
-                        {
-                            result.push(c);
-                            break false;
-                        }
-                    }
-                }
-            };
-            if retain {
-                remaining_ids.push(id);
+        let mut stream = self.stream_many(ids);
+        let mut result = Vec::with_capacity(stream.size_hint().0);
+        let mut error = None;
+        // drain everything, so all chunks end up in the cache even on errors
+        while let Some(res) = stream.next().await {
+            match res {
+                Ok(x) => result.push(x),
+                Err(e) => error = Some(e),
             }
         }
+        if let Some(e) = error {
+            return Err(e);
+        }
 
-        let result = Mutex::new(result);
-        let chunks = join_ids(&remaining_ids);
-        let futs: FuturesUnordered<_> = chunks
-            .into_iter()
-            .map(|rest| {
-                let rest = Some(format!("ids={rest}"));
-                async {
-                    let request =
-                        build_request::<T, _, Self, AUTHENTICATED, FORCE>(self, T::URL, rest)?;
-
-                    let response = exec_req::<Self, AUTHENTICATED, FORCE>(self, request).await?;
-                    let mut result = result.lock().await;
-                    let index = result.len();
-                    // TODO: consider postponing the locking
-                    cache_response_many(self, response, &mut result).await?;
-
-                    for x in result.iter().skip(index) {
-                        let (_, tx) = txs
-                            .remove(x.id())
-                            .expect("received unexpected entry from api");
-                        // ignoring the error is fine here
-                        // the receiving side will check the cache if nothing got sent
-                        let _ = tx.lock().await.send(x.clone());
-                    }
-                    Result::<(), EndpointError>::Ok(())
+        Ok(result)
+    }
+
+    /// request multiple ids at once, yielding entries as soon as their chunk
+    /// arrives
+    ///
+    /// Entries from cache come first, followed by the requested chunks in the
+    /// order they complete and finally entries that were already being
+    /// requested elsewhere.
+    /// A failed chunk yields a single error and the stream continues with
+    /// the remaining chunks.
+    fn stream_many<
+        'a,
+        T: DeserializeOwned
+            + Serialize
+            + EndpointWithId<IdType = I>
+            + BulkEndpoint
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        I: Display + DeserializeOwned + Hash + Clone + Eq + Send + Sync + 'static,
+    >(
+        &'a self,
+        ids: Vec<impl Into<I> + Send + 'a>,
+    ) -> BoxStream<'a, EndpointResult<T>> {
+        let ids = ids.into_iter().map(Into::into).collect();
+        stream_bulk::<T, _, Self, AUTHENTICATED, FORCE>(self, Ids::new(), ids)
+            .filter_map(|res| async move {
+                match res {
+                    Ok(Either::Left(found)) => Some(Ok(found)),
+                    Ok(Either::Right(_missing)) => None,
+                    Err(e) => Some(Err(e)),
                 }
             })
-            .collect();
-        let mut futs = futs.into_future();
+            .boxed()
+    }
+
+    /// request multiple ids at once, reporting the ids the api does not know
+    ///
+    /// Unknown ids are remembered for the duration set with
+    /// [`Client::cache_not_found`], so they don't get requested again in the
+    /// meantime.
+    #[cfg_attr(feature = "tracing", instrument(skip_all, fields(endpoint = %T::URL)))]
+    async fn many_detailed<
+        T: DeserializeOwned
+            + Serialize
+            + EndpointWithId<IdType = I>
+            + BulkEndpoint
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        I: Display + DeserializeOwned + Hash + Clone + Eq + Send + Sync + 'static,
+    >(
+        &self,
+        ids: Vec<impl Into<I> + Send>,
+    ) -> EndpointResult<ManyResult<T, I>> {
+        let ids = ids.into_iter().map(Into::into).collect();
+        let mut stream = stream_bulk::<T, _, Self, AUTHENTICATED, FORCE>(self, Ids::new(), ids);
+        let mut result = ManyResult {
+            found: Vec::with_capacity(stream.size_hint().0),
+            missing: Vec::new(),
+        };
         let mut error = None;
-        while let (Some(res), fut) = futs.await {
-            futs = fut.into_future();
-            if let Err(e) = res {
-                error = Some(e);
+        // drain everything, so all chunks end up in the cache even on errors
+        while let Some(res) = stream.next().await {
+            match res {
+                Ok(Either::Left(found)) => result.found.push(found),
+                Ok(Either::Right(missing)) => result.missing.push(missing),
+                Err(e) => error = Some(e),
             }
         }
         if let Some(e) = error {
             return Err(e);
         }
 
-        let mut result = result.into_inner();
-        for mut rx in rxs {
-            // TODO: check cache again
-            result.push(rx.recv().await?);
-        }
-
         Ok(result)
     }
 
-    /// requests a page of items and returns the number of total items across
-    /// all pages
+    /// requests a page of items
+    ///
+    /// pages are not cached
     #[cfg_attr(feature = "tracing", instrument(skip_all, fields(endpoint = %T::URL)))]
     async fn page<T: DeserializeOwned + PagedEndpoint + Clone + Send + Sync + 'static>(
         &self,
         page: usize,
         page_size: u8,
-        result: &mut Vec<T>,
-    ) -> EndpointResult<usize> {
+    ) -> EndpointResult<Page<T>> {
         let queries = format!("page={page}&page_size={page_size}");
         let request =
             build_request::<T, _, Self, AUTHENTICATED, FORCE>(self, T::URL, Some(queries))?;
 
         let response = exec_req::<Self, AUTHENTICATED, FORCE>(self, request).await?;
-        let count = get_header(&response, "x-result-total").unwrap_or(0);
-        let (_expires, res): (_, Vec<T>) = parse_response(self, response).await?;
-        result.extend_from_slice(&res);
+        let mut result = Page {
+            items: Vec::new(),
+            page,
+            page_total: get_header(&response, "x-page-total").unwrap_or(1),
+            page_size: get_header(&response, "x-page-size").unwrap_or(page_size.into()),
+            result_count: get_header(&response, "x-result-count").unwrap_or(0),
+            result_total: get_header(&response, "x-result-total").unwrap_or(0),
+            links: get_links(&response),
+        };
+        let (_expires, items) = parse_response(self, response).await?;
+        result.items = items;
 
-        Ok(count)
+        Ok(result)
+    }
+
+    /// requests every page, yielding them in order
+    ///
+    /// Up to four pages after the first one are requested at the same time,
+    /// going through the rate limiter like any other request.
+    fn pages<'a, T: DeserializeOwned + PagedEndpoint + Clone + Send + Sync + 'static>(
+        &'a self,
+        page_size: u8,
+    ) -> BoxStream<'a, EndpointResult<Page<T>>> {
+        stream::once(self.page::<T>(0, page_size))
+            .flat_map(move |res| match res {
+                Ok(first) => {
+                    let rest = stream::iter(1..first.page_total)
+                        .map(move |page| self.page::<T>(page, page_size))
+                        .buffered(PAGE_CONCURRENCY);
+                    stream::once(async { Ok(first) }).chain(rest).boxed()
+                }
+                Err(e) => stream::once(async { Err(e) }).boxed(),
+            })
+            .boxed()
     }
 
     /// requests all items using the most efficient method available
@@ -361,7 +653,7 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
             + Send
             + Sync
             + 'static,
-        I: Display + DeserializeOwned + Hash + Clone + Sync + 'static,
+        I: Display + DeserializeOwned + Hash + Clone + Eq + Send + Sync + 'static,
     >(
         &self,
     ) -> EndpointResult<Vec<T>> {
@@ -369,9 +661,10 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
             return Err(EndpointError::UnsupportedEndpointQuery);
         }
 
-        if let Some(c) =
-            check_cache::<Vec<T>, str, T, Self, AUTHENTICATED, FORCE>(self, "ids=all").await
-        {
+        let cached =
+            check_cache::<Vec<T>, str, T, Self, AUTHENTICATED, FORCE>(self, "ids=all").await;
+        record_lookup::<Self, AUTHENTICATED, FORCE>(self, T::NAME, cached.is_some());
+        if let Some(c) = cached {
             return Ok(c);
         }
 
@@ -381,7 +674,7 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
         let response = exec_req::<Self, AUTHENTICATED, FORCE>(self, request).await?;
         let count = get_header(&response, "x-result-total").unwrap_or(0);
         let mut result = Vec::with_capacity(count);
-        cache_response_all(self, response, &mut result).await?;
+        cache_response_all(self, &Ids::new(), "ids=all", response, &mut result).await?;
 
         Ok(result)
     }
@@ -395,14 +688,12 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
     >(
         &self,
     ) -> EndpointResult<Vec<T>> {
-        let mut result = Vec::with_capacity(200);
-        let max_items = self.page(0, 200, &mut result).await?;
-        let remaining = max_items.saturating_sub(200);
-        result.reserve_exact(remaining);
-
-        let pages = ((remaining as f64) / 200_f64).ceil() as usize;
-        for page in 0..pages {
-            self.page(page + 1, 200, &mut result).await?;
+        let mut pages = self.pages::<T>(200);
+        let mut result = Vec::new();
+        while let Some(page) = pages.next().await {
+            let page = page?;
+            result.reserve_exact(page.result_total.saturating_sub(result.len()));
+            result.extend(page.items);
         }
 
         Ok(result)
@@ -428,52 +719,307 @@ pub trait Requester<const AUTHENTICATED: bool, const FORCE: bool>: Sized + Sync
         let ids = self.ids::<T, I>().await?;
         self.many(ids).await
     }
-}
 
-struct SenderGuard<'client, T: Send> {
-    sender: Arc<Mutex<Sender<T>>>,
-    inflight: &'client Inflight,
-    hash: (TypeId, u64),
-}
+    /// requests all items, yielding entries as soon as their chunk arrives
+    ///
+    /// uses the same method as [`Self::all`], so endpoints supporting
+    /// `ids=all` yield everything at once after a single request
+    fn stream_all<
+        'a,
+        T: DeserializeOwned
+            + Serialize
+            + EndpointWithId<IdType = I>
+            + BulkEndpoint
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        I: Display + DeserializeOwned + Serialize + Hash + Clone + Send + Sync + Eq + 'static,
+    >(
+        &'a self,
+    ) -> BoxStream<'a, EndpointResult<T>> {
+        if T::ALL {
+            stream::once(self.get_all_by_ids_all())
+                .flat_map(|res| {
+                    stream::iter(match res {
+                        Ok(all) => Either::Left(all.into_iter().map(Ok)),
+                        Err(e) => Either::Right(std::iter::once(Err(e))),
+                    })
+                })
+                .boxed()
+        } else {
+            stream::once(self.ids::<T, I>())
+                .flat_map(move |res| match res {
+                    Ok(ids) => self.stream_many(ids),
+                    Err(e) => stream::once(async { Err(e) }).boxed(),
+                })
+                .boxed()
+        }
+    }
 
-impl<T: Send> Deref for SenderGuard<'_, T> {
-    type Target = Mutex<Sender<T>>;
+    /// request a resource below a parent, like the members of a guild
+    ///
+    /// responses are cached per parent
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{model::guild::members::GuildMembers, Client, Requester};
+    ///
+    /// # async fn run() -> Result<(), gw2lib::EndpointError> {
+    /// let client = Client::default().api_key("<api key>");
+    /// # #[cfg(not(feature = "blocking"))]
+    /// let members: GuildMembers = client.scoped("<guild id>").await?;
+    /// # #[cfg(feature = "blocking")]
+    /// # let members: GuildMembers = client.scoped("<guild id>".to_string())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", instrument(skip_all, fields(endpoint = %T::URL, path = %T::PATH)))]
+    async fn scoped<
+        T: DeserializeOwned + Serialize + ParentEndpoint<ParentId = P> + Clone + Send + Sync + 'static,
+        P: Display,
+    >(
+        &self,
+        parent: impl Into<P> + Send,
+    ) -> EndpointResult<T> {
+        let url = T::parent_url(&T::format_parent(&parent.into()));
+        get_or_ids::<T, T, Self, AUTHENTICATED, FORCE>(self, T::NAME, &url, &url, "").await
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.sender
+    /// request all ids available below a parent
+    #[cfg_attr(feature = "tracing", instrument(skip_all, fields(endpoint = %T::URL, path = %T::PATH)))]
+    async fn scoped_ids<
+        T: DeserializeOwned
+            + Serialize
+            + ParentBulkEndpoint<ParentId = P, IdType = I>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        P: Display,
+        I: Display + DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+    >(
+        &self,
+        parent: impl Into<P> + Send,
+    ) -> EndpointResult<Vec<I>> {
+        let url = T::parent_url(&T::format_parent(&parent.into()));
+        get_or_ids::<T, Vec<I>, Self, AUTHENTICATED, FORCE>(self, T::NAME, &url, &url, "").await
     }
-}
 
-impl<T: Send> Drop for SenderGuard<'_, T> {
-    fn drop(&mut self) {
-        let inflight = self.inflight.clone();
-        let hash = self.hash;
+    /// request a single item below a parent
+    /// ## Example
+    /// ```no_run
+    /// use gw2lib::{model::authenticated::characters::BuildTab, Client, Requester};
+    ///
+    /// # async fn run() -> Result<(), gw2lib::EndpointError> {
+    /// let client = Client::default().api_key("<api key>");
+    /// # #[cfg(not(feature = "blocking"))]
+    /// let tab: BuildTab = client.scoped_single("My Character", 1_usize).await?;
+    /// # #[cfg(feature = "blocking")]
+    /// # let tab: BuildTab = client.scoped_single("My Character".to_string(), 1_usize)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", instrument(skip_all, fields(endpoint = %T::URL, path = %T::PATH)))]
+    async fn scoped_single<
+        T: DeserializeOwned
+            + Serialize
+            + ParentBulkEndpoint<ParentId = P, IdType = I>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        P: Display,
+        I: Display,
+    >(
+        &self,
+        parent: impl Into<P> + Send,
+        id: impl Into<I> + Send,
+    ) -> EndpointResult<T> {
+        let url = T::parent_url(&T::format_parent(&parent.into()));
+        let url = format!("{url}/{}", T::format_id(&id.into()));
+        get_or_ids::<T, T, Self, AUTHENTICATED, FORCE>(self, T::NAME, &url, &url, "").await
+    }
+
+    /// request multiple items below a parent at once
+    ///
+    /// Works like [`Self::many`]: every item is cached on its own and only
+    /// the ones missing from cache get requested. Items come back in the
+    /// order of `ids`, ids the api does not know are left out.
+    #[cfg_attr(feature = "tracing", instrument(skip_all, fields(endpoint = %T::URL, path = %T::PATH)))]
+    async fn scoped_many<
+        T: DeserializeOwned
+            + Serialize
+            + ParentBulkEndpoint<ParentId = P, IdType = I>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        P: Display,
+        I: Display,
+    >(
+        &self,
+        parent: impl Into<P> + Send,
+        ids: Vec<impl Into<I> + Send>,
+    ) -> EndpointResult<Vec<T>> {
+        let scope = Scoped::<T>::new(&parent.into());
+        let ids: Vec<String> = ids.into_iter().map(|id| scope.key_of(&id.into())).collect();
+        let mut order = HashMap::with_capacity(ids.len());
+        for (i, id) in ids.iter().enumerate() {
+            order.entry(id.clone()).or_insert(i);
+        }
 
-        let task = async move { inflight.remove(&hash) };
+        let mut stream = stream_bulk::<T, _, Self, AUTHENTICATED, FORCE>(self, scope.clone(), ids);
+        let mut result = Vec::with_capacity(order.len());
+        let mut error = None;
+        // drain everything, so all chunks end up in the cache even on errors
+        while let Some(res) = stream.next().await {
+            match res {
+                Ok(Either::Left(found)) => result.push(found),
+                Ok(Either::Right(_missing)) => {}
+                Err(e) => error = Some(e),
+            }
+        }
+        if let Some(e) = error {
+            return Err(e);
+        }
 
-        crate::block::spawn(task);
+        result.sort_by_cached_key(|x| order.get(&scope.key(x)).copied());
+        Ok(result)
     }
-}
 
-#[cfg_attr(feature = "tracing", instrument(name = "check inflight", skip_all, fields(endpoint = %T::URL)))]
-async fn check_inflight<
-    'client,
-    H: Send + Clone + 'static,
-    I: 'static + Hash,
-    T: Endpoint + Send + 'static,
-    A: 'static + Hash,
->(
-    inflight: &'client Inflight,
-    id: &I,
-    lang: Language,
-    auth: &Option<A>,
-) -> Option<Either<Receiver<H>, SenderGuard<'client, H>>> {
+    /// request all items below a parent
+    ///
+    /// uses `ids=all` if the endpoint supports it, otherwise
+    /// [`Self::scoped_ids`] followed by [`Self::scoped_many`]
+    #[cfg_attr(feature = "tracing", instrument(skip_all, fields(endpoint = %T::URL, path = %T::PATH)))]
+    async fn scoped_all<
+        T: DeserializeOwned
+            + Serialize
+            + ParentBulkEndpoint<ParentId = P, IdType = I>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        P: Display + Clone + Send + Sync,
+        I: Display + DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+    >(
+        &self,
+        parent: impl Into<P> + Send,
+    ) -> EndpointResult<Vec<T>> {
+        let parent = parent.into();
+        if !T::ALL {
+            let ids = self.scoped_ids::<T, P, I>(parent.clone()).await?;
+            return self.scoped_many::<T, P, I>(parent, ids).await;
+        }
+
+        let scope = Scoped::<T>::new(&parent);
+        let query = format!("{}=all", T::IDS);
+        let key = format!("{}?{query}", scope.url());
+        let cached = check_cache::<Vec<T>, str, T, Self, AUTHENTICATED, FORCE>(self, &key).await;
+        record_lookup::<Self, AUTHENTICATED, FORCE>(self, T::NAME, cached.is_some());
+        if let Some(c) = cached {
+            return Ok(c);
+        }
+
+        let request =
+            build_request::<T, _, Self, AUTHENTICATED, FORCE>(self, scope.url(), Some(query))?;
+        let response = exec_req::<Self, AUTHENTICATED, FORCE>(self, request).await?;
+        let count = get_header(&response, "x-result-total").unwrap_or(0);
+        let mut result = Vec::with_capacity(count);
+        cache_response_all(self, &scope, &key, response, &mut result).await?;
+
+        Ok(result)
+    }
+}
+
+/// what gets sent to everyone waiting for an inflight request
+type InflightResult<T> = Result<T, InflightError>;
+
+/// the errors of an inflight request that can be shared with everyone waiting
+/// for it
+#[derive(Clone, Debug)]
+enum InflightError {
+    Api(ApiError, Box<ErrorContext>),
+    Timeout,
+}
+
+impl InflightError {
+    /// returns nothing for errors that can't be cloned
+    fn from_endpoint_error(error: &EndpointError) -> Option<Self> {
+        match error {
+            EndpointError::ApiError(e, context) => Some(Self::Api(e.clone(), context.clone())),
+            EndpointError::Timeout => Some(Self::Timeout),
+            _ => None,
+        }
+    }
+}
+
+impl From<InflightError> for EndpointError {
+    fn from(error: InflightError) -> Self {
+        match error {
+            InflightError::Api(e, context) => EndpointError::ApiError(e, context),
+            InflightError::Timeout => EndpointError::Timeout,
+        }
+    }
+}
+
+/// either waits for an inflight request or is responsible for sending one
+type InflightEntry<'client, T> =
+    Either<Receiver<InflightResult<T>>, SenderGuard<'client, InflightResult<T>>>;
+
+struct SenderGuard<'client, T: Send + 'static> {
+    sender: Arc<Mutex<Sender<T>>>,
+    inflight: &'client Inflight,
+    hash: (TypeId, u64),
+}
+
+impl<T: Send + 'static> Deref for SenderGuard<'_, T> {
+    type Target = Mutex<Sender<T>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.sender
+    }
+}
+
+impl<T: Send + 'static> Drop for SenderGuard<'_, T> {
+    /// closes the channel, waking everyone still waiting with an error, and
+    /// removes the entry unless another request replaced it already
+    fn drop(&mut self) {
+        let inflight = self.inflight.clone();
+        let hash = self.hash;
+        let sender = Arc::downgrade(&self.sender);
+
+        let task = async move {
+            inflight.remove_if(&hash, |_, entry| {
+                entry
+                    .downcast_ref::<Weak<Mutex<Sender<T>>>>()
+                    .is_some_and(|x| x.ptr_eq(&sender))
+            })
+        };
+
+        crate::block::spawn(task);
+    }
+}
+
+#[cfg_attr(feature = "tracing", instrument(name = "check inflight", skip_all, fields(endpoint = %T::URL)))]
+async fn check_inflight<
+    'client,
+    H: Send + Clone + 'static,
+    I: 'static + Hash + ?Sized,
+    T: Endpoint + Send + 'static,
+    A: 'static + Hash,
+>(
+    inflight: &'client Inflight,
+    id: &I,
+    lang: Language,
+    auth: &Option<A>,
+) -> Option<InflightEntry<'client, H>> {
     let hash = hash::<_, H, I, A>(inflight.hasher(), id, T::LOCALE.then_some(lang), auth);
     Some(match inflight.entry(hash) {
         Entry::Occupied(mut e) => {
             let r = e
                 .get_mut()
-                .downcast_mut::<Weak<Mutex<Sender<H>>>>()
+                .downcast_mut::<Weak<Mutex<Sender<InflightResult<H>>>>>()
                 .unwrap();
             let r = r.upgrade()?;
             let r = r.lock().await;
@@ -507,13 +1053,214 @@ async fn check_cache<
     if !F {
         req.client()
             .cache
-            .get::<T, I, E, String>(id, req.client().language, &req.client().identifier)
+            .get::<T, I, E, String>(id, req.request_language(), &req.client().identifier)
             .await
     } else {
         None
     }
 }
 
+/// returns the cached entry for revalidation and the [`CachePolicy`], even if
+/// it already expired
+async fn check_stale<
+    K: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+    I: Display + Hash + Sync + 'static + ?Sized,
+    E: Endpoint,
+    Req: Requester<A, F>,
+    const A: bool,
+    const F: bool,
+>(
+    req: &Req,
+    id: &I,
+) -> Option<CacheEntry<K>> {
+    if F {
+        return None;
+    }
+    req.client()
+        .cache
+        .get_stale::<K, I, E, String>(id, req.request_language(), &req.client().identifier)
+        .await
+}
+
+/// counts a cache lookup for [`crate::Client::stats`], unless the request is
+/// forced
+fn record_lookup<Req: Requester<A, F>, const A: bool, const F: bool>(
+    req: &Req,
+    endpoint: &str,
+    hit: bool,
+) {
+    #[cfg(feature = "metrics")]
+    if !F {
+        req.client().metrics().cache_lookup(endpoint, hit);
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = (req, endpoint, hit);
+}
+
+/// counts waiting for a request already in flight for
+/// [`crate::Client::stats`]
+fn record_inflight_join<Req: Requester<A, F>, const A: bool, const F: bool>(
+    req: &Req,
+    endpoint: &str,
+) {
+    #[cfg(feature = "metrics")]
+    req.client().metrics().inflight_join(endpoint);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (req, endpoint);
+}
+
+/// makes the request conditional on the validators of the stale entry
+fn add_validators<K>(request: &mut Request<hyper::Body>, stale: Option<&CacheEntry<K>>) {
+    let Some(stale) = stale else {
+        return;
+    };
+    let headers = request.headers_mut();
+    let validators = [
+        (IF_NONE_MATCH, &stale.validators.etag),
+        (IF_MODIFIED_SINCE, &stale.validators.last_modified),
+    ];
+    for (name, value) in validators {
+        if let Some(value) = value.as_ref().and_then(|x| HeaderValue::from_str(x).ok()) {
+            headers.insert(name, value);
+        }
+    }
+}
+
+/// whether the api failed in a way the [`CachePolicy`] may cover up
+fn is_upstream_error(error: &EndpointError) -> bool {
+    match error {
+        EndpointError::RequestFailed(_) | EndpointError::Timeout => true,
+        EndpointError::ApiError(_, context) => context.status.is_server_error(),
+        _ => false,
+    }
+}
+
+/// replaces upstream errors with the stale entry if the policy allows it
+fn serve_stale_on_error<K>(
+    policy: &CachePolicy,
+    result: EndpointResult<K>,
+    stale: Option<CacheEntry<K>>,
+) -> EndpointResult<K> {
+    match (result, stale) {
+        (Err(e), Some(stale)) if is_upstream_error(&e) && policy.serve_on_error(&stale) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(error = %e, "serving stale cache entry");
+            Ok(stale.value)
+        }
+        (result, _) => result,
+    }
+}
+
+/// what to tell everyone waiting for the same request
+///
+/// returns nothing for errors that can't be cloned, closing the channel
+/// instead
+fn to_inflight<K: Clone>(result: &EndpointResult<K>) -> Option<InflightResult<K>> {
+    match result {
+        Ok(x) => Some(Ok(x.clone())),
+        Err(e) => InflightError::from_endpoint_error(e).map(Err),
+    }
+}
+
+/// refreshes an entry served by [`CachePolicy::stale_while_revalidate`] in the
+/// background
+///
+/// the refresh goes through the inflight map and revalidates, but never
+/// serves stale entries itself
+fn refresh_single<
+    T: DeserializeOwned + Serialize + Clone + Send + Sync + EndpointWithId<IdType = I> + 'static,
+    I: Display + DeserializeOwned + Hash + Send + Sync + Clone + 'static,
+    Req: Requester<A, F>,
+    const A: bool,
+    const F: bool,
+>(
+    req: &Req,
+    id: I,
+) {
+    let client = req.client().clone();
+    let cache_duration = req.cache_duration();
+    let language = req.request_language();
+    crate::block::spawn(async move {
+        let req = CachedRequest::<_, _, _, A, false> {
+            client: &client,
+            cache_duration,
+            cache_policy: CachePolicy::default(),
+            priority: Priority::Low,
+            deadline: None,
+            language,
+        };
+        let _ = req.single::<T, I>(id).await;
+    });
+}
+
+/// see [`refresh_single`]
+fn refresh_get_or_ids<
+    T: DeserializeOwned + Serialize + Endpoint + Clone + Send + Sync + 'static,
+    K: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+    Req: Requester<A, F>,
+    const A: bool,
+    const F: bool,
+>(
+    req: &Req,
+    name: &str,
+    key: &str,
+    path: &str,
+    query: &str,
+) {
+    let client = req.client().clone();
+    let cache_duration = req.cache_duration();
+    let language = req.request_language();
+    let (name, key, path, query) = (
+        name.to_string(),
+        key.to_string(),
+        path.to_string(),
+        query.to_string(),
+    );
+    crate::block::spawn(async move {
+        let req = CachedRequest::<_, _, _, A, false> {
+            client: &client,
+            cache_duration,
+            cache_policy: CachePolicy::default(),
+            priority: Priority::Low,
+            deadline: None,
+            language,
+        };
+        let _ = get_or_ids::<T, K, _, A, false>(&req, &name, &key, &path, &query).await;
+    });
+}
+
+/// see [`refresh_single`]
+fn refresh_bulk<
+    T: DeserializeOwned + Serialize + Endpoint + Clone + Send + Sync + 'static,
+    S: BulkScope<T>,
+    Req: Requester<A, F>,
+    const A: bool,
+    const F: bool,
+>(
+    req: &Req,
+    scope: S,
+    ids: Vec<S::Key>,
+) {
+    let client = req.client().clone();
+    let cache_duration = req.cache_duration();
+    let language = req.request_language();
+    crate::block::spawn(async move {
+        let req = CachedRequest::<_, _, _, A, false> {
+            client: &client,
+            cache_duration,
+            cache_policy: CachePolicy::default(),
+            priority: Priority::Low,
+            deadline: None,
+            language,
+        };
+        stream_bulk::<T, S, _, A, false>(&req, scope, ids)
+            .for_each(|_| future::ready(()))
+            .await;
+    });
+}
+
+/// requests `path` with the optional `query`, caching the response as a whole
+/// under `key`
 async fn get_or_ids<
     T: DeserializeOwned + Serialize + Endpoint + Clone + Send + Sync + 'static,
     K: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
@@ -522,49 +1269,173 @@ async fn get_or_ids<
     const F: bool,
 >(
     req: &Req,
+    name: &str,
+    key: &str,
+    path: &str,
+    query: &str,
 ) -> EndpointResult<K> {
-    let lang = req.client().language;
-    if let Some(c) = check_cache::<K, str, T, Req, A, F>(req, "").await {
+    let lang = req.request_language();
+    let cached = check_cache::<K, str, T, Req, A, F>(req, key).await;
+    record_lookup::<Req, A, F>(req, name, cached.is_some());
+    if let Some(c) = cached {
         return Ok(c);
     }
+    let stale = check_stale::<K, str, T, Req, A, F>(req, key).await;
+    if let Some(stale) = stale.filter(|x| req.policy().serve_while_revalidating(x)) {
+        refresh_get_or_ids::<T, K, Req, A, F>(req, name, key, path, query);
+        return Ok(stale.value);
+    }
 
     let tx = loop {
-        let either = check_inflight::<K, (), T, String>(
+        let either = check_inflight::<K, str, T, String>(
             &req.client().inflight,
-            &(),
+            key,
             lang,
             &req.client().identifier,
         )
         .await;
         match either {
-            Some(Either::Left(mut rx)) => return rx.recv().await.map_err(Into::into),
+            Some(Either::Left(mut rx)) => {
+                record_inflight_join::<Req, A, F>(req, name);
+                let received = with_deadline(req, async { Ok(rx.recv().await?) }).await?;
+                return received.map_err(Into::into);
+            }
             Some(Either::Right(tx)) => break tx,
             None => {
-                if let Some(c) = check_cache::<K, str, T, Req, A, F>(req, "").await {
+                if let Some(c) = check_cache::<K, str, T, Req, A, F>(req, key).await {
                     return Ok(c);
                 }
             }
         }
     };
 
-    let request = build_request::<T, String, Req, A, F>(req, T::URL, None)?;
+    let stale = check_stale::<K, str, T, Req, A, F>(req, key).await;
+    let result = async {
+        let extra = (!query.is_empty()).then_some(query);
+        let mut request = build_request::<T, _, Req, A, F>(req, path, extra)?;
+        if name != T::NAME {
+            if let Some(info) = request.extensions_mut().get_mut::<EndpointInfo>() {
+                info.name = Cow::Owned(name.to_owned());
+            }
+        }
+        add_validators(&mut request, stale.as_ref());
 
-    let response = exec_req::<Req, A, F>(req, request).await?;
-    let result = cache_response::<str, K, T, Req, A, F>(req, "", response).await?;
-    // ignoring the error is fine here
-    // the receiving side will check the cache if nothing got sent
-    let _ = tx.lock().await.send(result.clone());
+        let response = exec_req::<Req, A, F>(req, request).await?;
+        cache_response_or_revalidate::<str, K, T, Req, A, F>(req, key, response, stale.as_ref())
+            .await
+    }
+    .await;
+    let result = serve_stale_on_error(req.policy(), result, stale);
+    if let Some(inflight) = to_inflight(&result) {
+        // ignoring the error is fine here
+        // the receiving side will check the cache if nothing got sent
+        let _ = tx.lock().await.send(inflight);
+    }
 
-    Ok(result)
+    result
 }
 
+/// sends the request and reads the whole response body, retrying according
+/// to the client's [`crate::retry::RetryPolicy`]
 #[cfg_attr(feature = "tracing", instrument(name = "execute request", skip_all, fields(uri = %request.uri().path())))]
 async fn exec_req<Req: Requester<A, F>, const A: bool, const F: bool>(
     req: &Req,
     request: Request<hyper::Body>,
-) -> EndpointResult<Response<hyper::Body>> {
-    wait_for_rate_limit(req).await?;
+) -> EndpointResult<Response<Bytes>> {
+    let info = request_info(&request);
+    let mut response = with_deadline(req, exec_req_inner(req, request)).await?;
+    response.extensions_mut().insert(info);
+    Ok(response)
+}
+
+async fn exec_req_inner<Req: Requester<A, F>, const A: bool, const F: bool>(
+    req: &Req,
+    request: Request<hyper::Body>,
+) -> EndpointResult<Response<Bytes>> {
+    #[cfg(feature = "testing")]
+    if let Some(cassette) = &req.client().cassette {
+        if cassette.is_replaying() {
+            return cassette.play(request.uri());
+        }
+        let uri = request.uri().clone();
+        let response = exec_live(req, request).await?;
+        cassette.store(&uri, &response)?;
+        return Ok(response);
+    }
+
+    exec_live(req, request).await
+}
+
+async fn exec_live<Req: Requester<A, F>, const A: bool, const F: bool>(
+    req: &Req,
+    request: Request<hyper::Body>,
+) -> EndpointResult<Response<Bytes>> {
+    let policy = &req.client().retry;
+    let info = request.extensions().get::<EndpointInfo>().cloned();
+    let ctx = rate_limit_context(req, info.as_ref());
+    let mut attempt = 1;
+    loop {
+        wait_for_rate_limit(req, &ctx).await?;
+
+        #[cfg(feature = "metrics")]
+        let start = std::time::Instant::now();
+        let response = match req.client().timeout {
+            Some(timeout) => {
+                tokio::time::timeout(timeout, send_request(req, clone_request(&request)))
+                    .await
+                    .unwrap_or(Err(EndpointError::Timeout))
+            }
+            None => send_request(req, clone_request(&request)).await,
+        };
+        #[cfg(feature = "metrics")]
+        req.client().metrics().upstream(
+            request
+                .extensions()
+                .get::<EndpointInfo>()
+                .map_or(request.uri().path(), |x| &x.name),
+            response.as_ref().ok().map(|x| x.status()),
+            start.elapsed(),
+        );
+
+        let delay = match response {
+            Ok(response) if policy.should_retry_status(attempt, response.status()) => {
+                if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                    let _ = req.client().rate_limiter.penalize(&ctx).await;
+                }
+                let retry_after =
+                    get_header(&response, "retry-after").map(std::time::Duration::from_secs);
+                #[cfg(feature = "tracing")]
+                tracing::warn!(attempt, status = %response.status(), "retrying gw2 request");
+                policy.delay(attempt, retry_after)
+            }
+            Err(EndpointError::RequestFailed(e)) if policy.should_retry_error(attempt, &e) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(attempt, error = %e, "retrying gw2 request");
+                policy.delay(attempt, None)
+            }
+            Err(EndpointError::Timeout) if policy.should_retry_timeout(attempt) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(attempt, "retrying timed out gw2 request");
+                policy.delay(attempt, None)
+            }
+            res => {
+                return res.map(|mut response| {
+                    if let Some(info) = info {
+                        response.extensions_mut().insert(info);
+                    }
+                    response
+                })
+            }
+        };
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
 
+async fn send_request<Req: Requester<A, F>, const A: bool, const F: bool>(
+    req: &Req,
+    request: Request<hyper::Body>,
+) -> EndpointResult<Response<Bytes>> {
     #[cfg(feature = "tracing")]
     let span = {
         let uri = request.uri().path();
@@ -585,12 +1456,118 @@ async fn exec_req<Req: Requester<A, F>, const A: bool, const F: bool>(
         span
     };
 
-    let fut = req.client().client.request(request);
+    let fut = async {
+        let (parts, body) = req.client().client.request(request).await?.into_parts();
+        let body = hyper::body::to_bytes(body).await?;
+        Ok(Response::from_parts(parts, body))
+    };
 
     #[cfg(feature = "tracing")]
     let fut = fut.instrument(span);
 
-    fut.await.map_err(Into::into)
+    fut.await
+}
+
+/// fails with [`EndpointError::Timeout`] if `fut` does not finish before the
+/// deadline of the call
+async fn with_deadline<T, Req: Requester<A, F>, const A: bool, const F: bool>(
+    req: &Req,
+    fut: impl Future<Output = EndpointResult<T>>,
+) -> EndpointResult<T> {
+    match req.call_deadline() {
+        Some(deadline) => tokio::time::timeout(deadline, fut)
+            .await
+            .unwrap_or(Err(EndpointError::Timeout)),
+        None => fut.await,
+    }
+}
+
+/// the endpoint a request or response belongs to, as paths may contain ids
+#[derive(Clone)]
+struct EndpointInfo {
+    url: &'static str,
+    /// see [`Endpoint::NAME`], the path for [`Requester::raw`]
+    name: Cow<'static, str>,
+    authenticated: bool,
+}
+
+/// the request a response answers, for the [`ErrorContext`] of its errors
+#[derive(Clone)]
+struct RequestInfo {
+    url: String,
+    ids: Vec<String>,
+}
+
+fn request_info(request: &Request<hyper::Body>) -> RequestInfo {
+    let uri = request.uri();
+    let url = uri.path_and_query().map_or("", |x| x.as_str()).to_string();
+    let decode = |id: &str| {
+        urlencoding::decode(id)
+            .map(|x| x.into_owned())
+            .unwrap_or_else(|_| id.to_string())
+    };
+    let mut ids: Vec<String> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .find_map(|x| x.strip_prefix("ids=").or_else(|| x.strip_prefix("id=")))
+        .map(|x| x.split(',').map(decode).collect())
+        .unwrap_or_default();
+    // single requests put the id into the path
+    let endpoint = request.extensions().get::<EndpointInfo>().map(|x| x.url);
+    let id = endpoint.and_then(|url| {
+        uri.path()
+            .trim_start_matches('/')
+            .strip_prefix(url)?
+            .strip_prefix('/')
+    });
+    if let (true, Some(id)) = (ids.is_empty(), id) {
+        ids.push(decode(id));
+    }
+    RequestInfo { url, ids }
+}
+
+/// the context of an error `response`
+fn error_context(response: &Response<Bytes>) -> Box<ErrorContext> {
+    let info = response.extensions().get::<RequestInfo>();
+    Box::new(ErrorContext {
+        url: info.map(|x| x.url.clone()).unwrap_or_default(),
+        ids: info.map(|x| x.ids.clone()).unwrap_or_default(),
+        status: response.status(),
+        headers: response.headers().clone(),
+    })
+}
+
+/// the context of an id the client already knows to be missing, found at
+/// `url`
+fn not_found_context(url: &str, id: &str) -> Box<ErrorContext> {
+    Box::new(ErrorContext {
+        url: format!("/{url}"),
+        ids: vec![id.to_string()],
+        status: StatusCode::NOT_FOUND,
+        headers: Default::default(),
+    })
+}
+
+/// the context passed to the rate limiter for a request to `info`
+fn rate_limit_context<'a, Req: Requester<A, F>, const A: bool, const F: bool>(
+    req: &'a Req,
+    info: Option<&'a EndpointInfo>,
+) -> RequestContext<'a> {
+    let authenticated = info.is_some_and(|x| x.authenticated);
+    RequestContext {
+        identifier: req.client().identifier.as_deref().filter(|_| authenticated),
+        endpoint: info.map_or("", |x| &x.name),
+    }
+}
+
+/// requests never carry a body, so they can be cloned for retries
+fn clone_request(request: &Request<hyper::Body>) -> Request<hyper::Body> {
+    let mut clone = Request::new(hyper::Body::empty());
+    *clone.method_mut() = request.method().clone();
+    *clone.uri_mut() = request.uri().clone();
+    *clone.headers_mut() = request.headers().clone();
+    clone
 }
 
 #[cfg_attr(
@@ -599,9 +1576,19 @@ async fn exec_req<Req: Requester<A, F>, const A: bool, const F: bool>(
 )]
 async fn wait_for_rate_limit<Req: Requester<A, F>, const A: bool, const F: bool>(
     req: &Req,
+    ctx: &RequestContext<'_>,
 ) -> EndpointResult<()> {
-    let time = req.client().rate_limiter.take(1).await?;
+    #[cfg(feature = "metrics")]
+    let start = std::time::Instant::now();
+    let _permit = req
+        .client()
+        .scheduler
+        .acquire(req.priority_class(), req.client().identifier.as_deref())
+        .await;
+    let time = req.client().rate_limiter.take(1, ctx).await?;
     tokio::time::sleep(time).await;
+    #[cfg(feature = "metrics")]
+    req.client().metrics().rate_limit_wait(start.elapsed());
     Ok(())
 }
 
@@ -631,12 +1618,7 @@ fn build_request<T: Endpoint, Q: AsRef<str>, Req: Requester<A, F>, const A: bool
 
     if T::LOCALE {
         pnq.push_str("&lang=");
-        pnq.push_str(client.language.as_str());
-    }
-
-    if T::AUTHENTICATED {
-        pnq.push_str("&access_token=");
-        pnq.push_str(client.api_key.as_ref().unwrap());
+        pnq.push_str(req.request_language().as_str());
     }
 
     let (scheme, host) = client.host.split_once("://").expect("invalid host");
@@ -647,37 +1629,404 @@ fn build_request<T: Endpoint, Q: AsRef<str>, Req: Requester<A, F>, const A: bool
         .build()
         .expect("invalid uri");
 
-    let request = hyper::Request::builder()
+    let mut request = hyper::Request::builder()
         .uri(uri)
         .body(hyper::Body::empty())
         .unwrap();
+    if T::AUTHENTICATED {
+        // sent as a header so the key stays out of uris, proxy logs and traces
+        let key = client.api_key.as_deref().unwrap();
+        let mut value = HeaderValue::from_str(&format!("Bearer {key}"))
+            .map_err(|_| EndpointError::NotAuthenticated)?;
+        value.set_sensitive(true);
+        request.headers_mut().insert(AUTHORIZATION, value);
+    }
+    request.extensions_mut().insert(EndpointInfo {
+        url: T::URL,
+        name: Cow::Borrowed(T::NAME),
+        authenticated: T::AUTHENTICATED,
+    });
 
     Ok(request)
 }
 
-/// returns the remaining ids not found in cache
+/// the entries a bulk request asks for, either ids of an endpoint or ids below
+/// a parent
+trait BulkScope<T>: Clone + Send + Sync + 'static {
+    /// identifies an entry in the cache and among inflight requests
+    type Key: Display + Hash + Clone + Eq + Send + Sync + 'static;
+
+    /// the url the entries are requested from
+    fn url(&self) -> &str;
+
+    /// the query parameter taking multiple ids
+    fn ids_param(&self) -> &'static str;
+
+    /// the id of `key` as sent to the api
+    fn format_key(&self, key: &Self::Key) -> String;
+
+    /// the key of an entry received from the api
+    fn key(&self, entry: &T) -> Self::Key;
+
+    /// the url of a single entry, for entries known to be missing
+    fn entry_url(&self, key: &Self::Key) -> String;
+}
+
+/// the ids of an endpoint, cached under the ids themselves
+struct Ids<T>(PhantomData<fn() -> T>);
+
+impl<T> Clone for Ids<T> {
+    fn clone(&self) -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Ids<T> {
+    fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: EndpointWithId + BulkEndpoint + 'static> BulkScope<T> for Ids<T>
+where
+    T::IdType: Hash + Clone + Eq + Send + Sync + 'static,
+{
+    type Key = T::IdType;
+
+    fn url(&self) -> &str {
+        T::URL
+    }
+
+    fn ids_param(&self) -> &'static str {
+        "ids"
+    }
+
+    fn format_key(&self, key: &Self::Key) -> String {
+        key.to_string()
+    }
+
+    fn key(&self, entry: &T) -> Self::Key {
+        entry.id().clone()
+    }
+
+    fn entry_url(&self, key: &Self::Key) -> String {
+        T::format_url(&T::format_id(key))
+    }
+}
+
+/// the ids below a parent, cached under `<parent url>/<id>` like
+/// [`Requester::scoped_single`]
+struct Scoped<T> {
+    url: Arc<str>,
+    endpoint: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Scoped<T> {
+    fn clone(&self) -> Self {
+        Self {
+            url: self.url.clone(),
+            endpoint: PhantomData,
+        }
+    }
+}
+
+impl<T: ParentBulkEndpoint> Scoped<T> {
+    fn new(parent: &T::ParentId) -> Self {
+        Self {
+            url: T::parent_url(&T::format_parent(parent)).into(),
+            endpoint: PhantomData,
+        }
+    }
+
+    fn key_of(&self, id: &T::IdType) -> String {
+        format!("{}/{}", self.url, T::format_id(id))
+    }
+}
+
+impl<T: ParentBulkEndpoint + 'static> BulkScope<T> for Scoped<T> {
+    type Key = String;
+
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn ids_param(&self) -> &'static str {
+        T::IDS
+    }
+
+    fn format_key(&self, key: &Self::Key) -> String {
+        key[self.url.len() + 1..].to_string()
+    }
+
+    fn key(&self, entry: &T) -> Self::Key {
+        self.key_of(entry.id())
+    }
+
+    fn entry_url(&self, key: &Self::Key) -> String {
+        key.clone()
+    }
+}
+
+/// requests multiple ids, yielding found entries on the left and ids the api
+/// does not know on the right
+fn stream_bulk<
+    'a,
+    T: DeserializeOwned + Serialize + Endpoint + Clone + Send + Sync + 'static,
+    S: BulkScope<T>,
+    Req: Requester<A, F>,
+    const A: bool,
+    const F: bool,
+>(
+    req: &'a Req,
+    scope: S,
+    ids: Vec<S::Key>,
+) -> BoxStream<'a, EndpointResult<Either<T, S::Key>>> {
+    let prepare = async move {
+        let mut cached = Vec::with_capacity(ids.len());
+        let ids = if !F {
+            let mut refresh = Vec::new();
+            let ids = extract_many_from_cache(req, ids, &mut cached, &mut refresh).await;
+            if !refresh.is_empty() {
+                refresh_bulk::<T, S, Req, A, F>(req, scope.clone(), refresh);
+            }
+            ids
+        } else {
+            ids
+        };
+
+        let mut pending = Vec::with_capacity(ids.len());
+        let mut rxs = Vec::new();
+        for id in ids {
+            loop {
+                let either = check_inflight::<T, S::Key, T, String>(
+                    &req.client().inflight,
+                    &id,
+                    req.request_language(),
+                    &req.client().identifier,
+                )
+                .await;
+                match either {
+                    Some(Either::Left(rx)) => {
+                        record_inflight_join::<Req, A, F>(req, T::NAME);
+                        rxs.push((id, rx));
+                        break;
+                    }
+                    Some(Either::Right(tx)) => {
+                        pending.push((id, tx));
+                        break;
+                    }
+                    None => {
+                        if let Some(c) = check_cache::<T, S::Key, T, Req, A, F>(req, &id).await {
+                            cached.push(Either::Left(c));
+                            break;
+                        }
+                        if check_missing::<T, S::Key, Req, A, F>(req, &id).await {
+                            cached.push(Either::Right(id));
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        // the api accepts up to 200 ids per request
+        let mut chunks = Vec::with_capacity(pending.len() / 200 + 1);
+        let mut pending = pending.into_iter().peekable();
+        while pending.peek().is_some() {
+            chunks.push(pending.by_ref().take(200).collect::<Vec<_>>());
+        }
+        let requested: FuturesUnordered<_> = chunks
+            .into_iter()
+            .map(|chunk| request_chunk::<T, S, Req, A, F>(req, scope.clone(), chunk))
+            .collect();
+        let requested = requested.flat_map(stream::iter);
+
+        let inflight = stream::iter(rxs).then(move |(id, mut rx)| async move {
+            let received = match with_deadline(req, async { Ok(rx.recv().await?) }).await {
+                // the request got dropped without sending, but it may have
+                // cached its result before
+                Err(EndpointError::InflightReceiveFailed(e)) => {
+                    if let Some(c) = check_cache::<T, S::Key, T, Req, A, F>(req, &id).await {
+                        return Ok(Either::Left(c));
+                    }
+                    if check_missing::<T, S::Key, Req, A, F>(req, &id).await {
+                        return Ok(Either::Right(id));
+                    }
+                    return Err(e.into());
+                }
+                res => res?,
+            };
+            match received {
+                Ok(found) => Ok(Either::Left(found)),
+                Err(InflightError::Api(ApiError::NotFound, _)) => Ok(Either::Right(id)),
+                Err(e) => Err(e.into()),
+            }
+        });
+
+        stream::iter(cached.into_iter().map(Ok))
+            .chain(requested)
+            .chain(inflight)
+    };
+
+    stream::once(prepare).flatten().boxed()
+}
+
+/// requests a chunk of ids and notifies everyone waiting for them
+async fn request_chunk<
+    'client,
+    T: DeserializeOwned + Serialize + Endpoint + Clone + Send + Sync + 'static,
+    S: BulkScope<T>,
+    Req: Requester<A, F>,
+    const A: bool,
+    const F: bool,
+>(
+    req: &'client Req,
+    scope: S,
+    chunk: Vec<(S::Key, SenderGuard<'client, InflightResult<T>>)>,
+) -> Vec<EndpointResult<Either<T, S::Key>>> {
+    let ids: Vec<S::Key> = chunk.iter().map(|(id, _)| id.clone()).collect();
+    let found = match fetch_chunk::<T, S, Req, A, F>(req, &scope, &ids).await {
+        Ok(found) => found,
+        Err(e) => return serve_chunk_on_error::<T, S::Key, Req, A, F>(req, chunk, e).await,
+    };
+
+    let mut txs: HashMap<S::Key, _> = chunk.into_iter().collect();
+    let mut result = Vec::with_capacity(txs.len());
+    for x in found {
+        let key = scope.key(&x);
+        // the api may answer with ids that were not asked for, or repeat one
+        let Some(tx) = txs.remove(&key) else {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(id = %key, "skipping unexpected entry from gw2 api");
+            continue;
+        };
+        // ignoring the error is fine here
+        // the receiving side will check the cache if nothing got sent
+        let _ = tx.lock().await.send(Ok(x.clone()));
+        result.push(Ok(Either::Left(x)));
+    }
+
+    for (id, tx) in txs {
+        cache_missing::<T, S::Key, Req, A, F>(req, &id).await;
+        let _ = tx.lock().await.send(Err(InflightError::Api(
+            ApiError::NotFound,
+            not_found_context(&scope.entry_url(&id), &scope.format_key(&id)),
+        )));
+        result.push(Ok(Either::Right(id)));
+    }
+
+    result
+}
+
+async fn fetch_chunk<
+    T: DeserializeOwned + Serialize + Endpoint + Clone + Send + Sync + 'static,
+    S: BulkScope<T>,
+    Req: Requester<A, F>,
+    const A: bool,
+    const F: bool,
+>(
+    req: &Req,
+    scope: &S,
+    ids: &[S::Key],
+) -> EndpointResult<Vec<T>> {
+    let formatted: Vec<String> = ids.iter().map(|id| scope.format_key(id)).collect();
+    let rest = Some(format!("{}={}", scope.ids_param(), join_ids(&formatted)));
+    let request = build_request::<T, _, Req, A, F>(req, scope.url(), rest)?;
+
+    let response = exec_req::<Req, A, F>(req, request).await?;
+    let mut found = Vec::with_capacity(ids.len());
+    // the api answers with 404 if none of the ids exist
+    if response.status() != StatusCode::NOT_FOUND {
+        cache_response_many(req, scope, response, &mut found).await?;
+    }
+    Ok(found)
+}
+
+/// serves stale entries for a failed chunk if the [`CachePolicy`] allows it,
+/// yielding the error once for all ids without one
+async fn serve_chunk_on_error<
+    'client,
+    T: DeserializeOwned + Serialize + Endpoint + Clone + Send + Sync + 'static,
+    I: Display + Hash + Clone + Eq + Sync + 'static,
+    Req: Requester<A, F>,
+    const A: bool,
+    const F: bool,
+>(
+    req: &'client Req,
+    chunk: Vec<(I, SenderGuard<'client, InflightResult<T>>)>,
+    error: EndpointError,
+) -> Vec<EndpointResult<Either<T, I>>> {
+    let serve_stale = is_upstream_error(&error) && req.policy().has_stale_if_error();
+    let mut result = Vec::with_capacity(chunk.len());
+    let mut failed = false;
+    for (id, tx) in chunk {
+        let stale = if serve_stale {
+            check_stale::<T, I, T, Req, A, F>(req, &id)
+                .await
+                .filter(|x| req.policy().serve_on_error(x))
+        } else {
+            None
+        };
+        match stale {
+            Some(stale) => {
+                let _ = tx.lock().await.send(Ok(stale.value.clone()));
+                result.push(Ok(Either::Left(stale.value)));
+            }
+            None => {
+                if let Some(e) = InflightError::from_endpoint_error(&error) {
+                    let _ = tx.lock().await.send(Err(e));
+                }
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        result.push(Err(error));
+    }
+    result
+}
+
+/// returns the remaining ids neither found in cache nor known to be missing
+///
+/// stale entries served according to the [`CachePolicy`] are added to
+/// `refresh`
 #[cfg_attr(feature = "tracing", instrument(name = "check cache many", skip_all, fields(endpoint = %K::URL)))]
 async fn extract_many_from_cache<
     I: Display + Hash + Sync + 'static,
-    K: EndpointWithId<IdType = I> + DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+    K: Endpoint + DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
     Req: Requester<A, F>,
     const A: bool,
     const F: bool,
 >(
     req: &Req,
-    ids: Vec<impl Into<I> + Send>,
-    result: &mut Vec<K>,
+    ids: Vec<I>,
+    result: &mut Vec<Either<K, I>>,
+    refresh: &mut Vec<I>,
 ) -> Vec<I> {
+    let cached = req
+        .client()
+        .cache
+        .get_many::<K, I, K, String>(&ids, req.request_language(), &req.client().identifier)
+        .await;
     let mut rest = Vec::with_capacity(ids.len());
-    for i in ids {
-        let i = i.into();
-        if let Some(cached) = req
-            .client()
-            .cache
-            .get::<K, I, K, String>(&i, req.client().language, &req.client().identifier)
-            .await
-        {
-            result.push(cached);
+    for (i, cached) in ids.into_iter().zip(cached) {
+        record_lookup::<Req, A, F>(req, K::NAME, cached.is_some());
+        if let Some(cached) = cached {
+            result.push(Either::Left(cached));
+            continue;
+        }
+        if req.policy().has_stale_while_revalidate() {
+            let stale = check_stale::<K, I, K, Req, A, F>(req, &i)
+                .await
+                .filter(|x| req.policy().serve_while_revalidating(x));
+            if let Some(stale) = stale {
+                result.push(Either::Left(stale.value));
+                refresh.push(i);
+                continue;
+            }
+        }
+        if check_missing::<K, I, Req, A, F>(req, &i).await {
+            result.push(Either::Right(i));
         } else {
             rest.push(i);
         }
@@ -685,6 +2034,107 @@ async fn extract_many_from_cache<
     rest
 }
 
+/// stands in for endpoints without a model, see [`Requester::raw`]
+#[derive(Clone, Serialize, Deserialize)]
+struct Raw<const A: bool>;
+
+impl<const A: bool> Endpoint for Raw<A> {
+    const AUTHENTICATED: bool = A;
+    const LOCALE: bool = false;
+    const URL: &'static str = "raw";
+    const VERSION: &'static str = "latest";
+}
+
+/// marks an id the api does not know about in the cache
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+struct NotFound<T>(PhantomData<T>);
+
+/// checks whether `id` is cached as unknown to the api
+async fn check_missing<
+    T: Endpoint + Clone + Send + Sync + 'static,
+    I: Display + Hash + Sync + 'static,
+    Req: Requester<A, F>,
+    const A: bool,
+    const F: bool,
+>(
+    req: &Req,
+    id: &I,
+) -> bool {
+    let id = format!("missing_{id}");
+    check_cache::<NotFound<T>, String, T, Req, A, F>(req, &id)
+        .await
+        .is_some()
+}
+
+/// remembers `id` as unknown to the api
+async fn cache_missing<
+    T: Endpoint + Clone + Send + Sync + 'static,
+    I: Display + Hash + Sync + 'static,
+    Req: Requester<A, F>,
+    const A: bool,
+    const F: bool,
+>(
+    req: &Req,
+    id: &I,
+) {
+    let duration = req.client().not_found_duration;
+    if duration.is_zero() {
+        return;
+    }
+    req.client()
+        .cache
+        .insert::<NotFound<T>, String, T, String>(
+            &format!("missing_{id}"),
+            &NotFound(PhantomData),
+            Utc::now().naive_utc() + duration,
+            req.request_language(),
+            &req.client().identifier,
+        )
+        .await;
+}
+
+/// caches the response, or extends the stale entry if the api answered that
+/// it did not change
+async fn cache_response_or_revalidate<
+    I: Hash + Sync + 'static + Display + ?Sized,
+    K: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
+    T: Endpoint,
+    Req: Requester<A, F>,
+    const A: bool,
+    const F: bool,
+>(
+    req: &Req,
+    id: &I,
+    response: Response<Bytes>,
+    stale: Option<&CacheEntry<K>>,
+) -> Result<K, EndpointError> {
+    let stale = match stale {
+        Some(stale) if response.status() == StatusCode::NOT_MODIFIED => stale,
+        _ => return cache_response::<I, K, T, Req, A, F>(req, id, response).await,
+    };
+
+    let expires = get_cache_expiry(req, &response);
+    let mut validators = get_validators(&response);
+    validators.etag = validators.etag.or_else(|| stale.validators.etag.clone());
+    validators.last_modified = validators
+        .last_modified
+        .or_else(|| stale.validators.last_modified.clone());
+    req.client()
+        .cache
+        .insert_validated::<K, I, T, String>(
+            id,
+            &stale.value,
+            expires,
+            &validators,
+            req.request_language(),
+            &req.client().identifier,
+        )
+        .await;
+
+    Ok(stale.value.clone())
+}
+
 async fn cache_response<
     I: Hash + Sync + 'static + Display + ?Sized,
     K: DeserializeOwned + Serialize + Clone + Send + Sync + 'static,
@@ -695,17 +2145,19 @@ async fn cache_response<
 >(
     req: &Req,
     id: &I,
-    response: Response<hyper::Body>,
+    response: Response<Bytes>,
 ) -> Result<K, EndpointError> {
+    let validators = get_validators(&response);
     let (expires, result): (_, K) = parse_response(req, response).await?;
 
     req.client()
         .cache
-        .insert::<K, I, T, String>(
+        .insert_validated::<K, I, T, String>(
             id,
             &result,
             expires,
-            req.client().language,
+            &validators,
+            req.request_language(),
             &req.client().identifier,
         )
         .await;
@@ -714,58 +2166,47 @@ async fn cache_response<
 }
 
 async fn cache_response_many<
-    I: Display + Hash + Sync + 'static,
-    K: DeserializeOwned
-        + Serialize
-        + BulkEndpoint
-        + EndpointWithId<IdType = I>
-        + Clone
-        + Send
-        + Sync
-        + 'static,
+    K: DeserializeOwned + Serialize + Endpoint + Clone + Send + Sync + 'static,
+    S: BulkScope<K>,
     Req: Requester<A, F>,
     const A: bool,
     const F: bool,
 >(
     req: &Req,
-    response: Response<hyper::Body>,
+    scope: &S,
+    response: Response<Bytes>,
     result: &mut Vec<K>,
 ) -> Result<(), EndpointError> {
     let (expires, res): (_, Vec<K>) = parse_response(req, response).await?;
 
-    for t in res {
-        req.client()
-            .cache
-            .insert::<K, I, K, String>(
-                t.id(),
-                &t,
-                expires,
-                req.client().language,
-                &req.client().identifier,
-            )
-            .await;
-        result.push(t);
-    }
+    let keys: Vec<_> = res.iter().map(|t| scope.key(t)).collect();
+    let entries: Vec<_> = keys.iter().zip(&res).collect();
+    req.client()
+        .cache
+        .insert_many::<K, S::Key, K, String>(
+            &entries,
+            expires,
+            req.request_language(),
+            &req.client().identifier,
+        )
+        .await;
+    result.extend(res);
 
     Ok(())
 }
 
+/// caches the whole response under `all` and every entry on its own
 async fn cache_response_all<
-    I: Display + Hash + Sync + 'static,
-    K: DeserializeOwned
-        + Serialize
-        + BulkEndpoint
-        + EndpointWithId<IdType = I>
-        + Clone
-        + Send
-        + Sync
-        + 'static,
+    K: DeserializeOwned + Serialize + Endpoint + Clone + Send + Sync + 'static,
+    S: BulkScope<K>,
     Req: Requester<A, F>,
     const A: bool,
     const F: bool,
 >(
     req: &Req,
-    response: Response<hyper::Body>,
+    scope: &S,
+    all: &str,
+    response: Response<Bytes>,
     result: &mut Vec<K>,
 ) -> Result<(), EndpointError> {
     let (expires, res): (_, Vec<K>) = parse_response(req, response).await?;
@@ -773,27 +2214,26 @@ async fn cache_response_all<
     req.client()
         .cache
         .insert::<Vec<K>, str, K, String>(
-            "ids=all",
+            all,
             &res,
             expires,
-            req.client().language,
+            req.request_language(),
             &req.client().identifier,
         )
         .await;
 
-    for t in res {
-        req.client()
-            .cache
-            .insert::<K, I, K, String>(
-                t.id(),
-                &t,
-                expires,
-                req.client().language,
-                &req.client().identifier,
-            )
-            .await;
-        result.push(t);
-    }
+    let keys: Vec<_> = res.iter().map(|t| scope.key(t)).collect();
+    let entries: Vec<_> = keys.iter().zip(&res).collect();
+    req.client()
+        .cache
+        .insert_many::<K, S::Key, K, String>(
+            &entries,
+            expires,
+            req.request_language(),
+            &req.client().identifier,
+        )
+        .await;
+    result.extend(res);
 
     Ok(())
 }
@@ -805,41 +2245,70 @@ async fn parse_response<
     const F: bool,
 >(
     req: &Req,
-    response: Response<hyper::Body>,
+    response: Response<Bytes>,
 ) -> Result<(NaiveDateTime, K), EndpointError> {
     let status = response.status();
+    let key = req.client().api_key.as_deref();
     if !status.is_success() {
-        let bytes = hyper::body::to_bytes(response.into_body()).await?;
-        let error = serde_json::from_slice::<'_, ErrorResponse>(&bytes);
-        return Err(EndpointError::ApiError(match (status.as_u16(), error) {
+        let info = response.extensions().get::<EndpointInfo>().cloned();
+        let context = error_context(&response);
+        let bytes = response.into_body();
+        let text = serde_json::from_slice::<'_, ErrorResponse>(&bytes).map(|x| x.text);
+        let error = match (status.as_u16(), text.as_deref()) {
             (401, _) => ApiError::Unauthorized,
-            (400, Ok(ErrorResponse { text })) if &text == "invalid key" => ApiError::Unauthorized,
-            (400, Ok(ErrorResponse { text })) if &text == "Invalid access token" => {
-                ApiError::Unauthorized
-            }
-            (400, Ok(ErrorResponse { text })) if &text == "account does not have game access" => {
-                ApiError::MissingGameAccess
-            }
+            (400, Ok("invalid key" | "Invalid access token")) => ApiError::Unauthorized,
+            (400, Ok("account does not have game access")) => ApiError::MissingGameAccess,
             (429, _) => {
-                let _ = req.client().rate_limiter.penalize().await;
+                let ctx = rate_limit_context(req, info.as_ref());
+                let _ = req.client().rate_limiter.penalize(&ctx).await;
                 ApiError::RateLimited
             }
-            (_, Ok(ErrorResponse { text })) => ApiError::Other(status, text),
-            _ => {
-                let body = String::from_utf8_lossy(&bytes);
-                ApiError::Other(status, body.to_string())
-            }
-        }));
+            (_, Ok(text)) if text.starts_with("page out of range") => ApiError::PageOutOfRange,
+            (_, Ok("API not active")) => ApiError::NotActive,
+            (404, _) | (_, Ok("no such id" | "all ids provided are invalid")) => ApiError::NotFound,
+            (503, _) => ApiError::Unavailable,
+            (_, Ok(text)) => ApiError::Other(status, redact(text, key)),
+            _ => ApiError::Other(status, redact(&String::from_utf8_lossy(&bytes), key)),
+        };
+        return Err(EndpointError::ApiError(error, context));
     }
     let expires = get_cache_expiry(req, &response);
-    let body = hyper::body::aggregate(response.into_body()).await?;
-    let result: K = serde_json::from_reader(&mut body.reader())?;
+    let mut deserializer = serde_json::Deserializer::from_slice(response.body());
+    let result: K = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let source = e.into_inner();
+        let excerpt = excerpt(response.body(), source.line(), source.column());
+        EndpointError::InvalidJsonResponse {
+            path,
+            excerpt: redact(&excerpt, key),
+            source,
+            context: error_context(&response),
+        }
+    })?;
     Ok((expires, result))
 }
 
+/// up to [`EXCERPT_LEN`] bytes of `body` around the given line and column
+fn excerpt(body: &[u8], line: usize, column: usize) -> String {
+    let offset = body
+        .split(|&x| x == b'\n')
+        .take(line.saturating_sub(1))
+        .map(|x| x.len() + 1)
+        .sum::<usize>()
+        + column;
+    let start = offset.saturating_sub(EXCERPT_LEN / 2).min(body.len());
+    let end = (start + EXCERPT_LEN).min(body.len());
+    String::from_utf8_lossy(&body[start..end]).into_owned()
+}
+
+const EXCERPT_LEN: usize = 200;
+
+/// how many pages [`Requester::pages`] requests at the same time
+const PAGE_CONCURRENCY: usize = 4;
+
 fn get_cache_expiry<Req: Requester<A, F>, const A: bool, const F: bool>(
     req: &Req,
-    response: &Response<hyper::Body>,
+    response: &Response<Bytes>,
 ) -> NaiveDateTime {
     let duration = req.cache_duration();
     let expires = if !duration.is_zero() {
@@ -851,31 +2320,65 @@ fn get_cache_expiry<Req: Requester<A, F>, const A: bool, const F: bool>(
 }
 
 /// concatenates ids, separated by comma: 1,2,3,4
-/// chunked in 200 per batch
 ///
 /// panics when `ids.len() == 0`
-fn join_ids<I: Display + 'static>(ids: &[I]) -> Vec<String> {
+fn join_ids<I: Display + 'static>(ids: &[I]) -> String {
     use std::fmt::Write;
-    let modulo = ids.len() % 200 != 0;
-    let ceil = ids.len() / 200 + (modulo as usize);
-    let mut result = Vec::with_capacity(ceil);
-    for ids in ids.chunks(200) {
-        let mut query_string = String::with_capacity(6 * ids.len()); // arbitrary. most ids are 5 digits + comma
-        write!(&mut query_string, "{}", ids[0]).expect("failed to concatenate ids");
-        for i in ids.iter().skip(1) {
-            write!(&mut query_string, ",{i}").expect("failed to concatenate ids");
-        }
-        result.push(query_string);
+    let mut query_string = String::with_capacity(6 * ids.len()); // arbitrary. most ids are 5 digits + comma
+    write!(&mut query_string, "{}", ids[0]).expect("failed to concatenate ids");
+    for i in ids.iter().skip(1) {
+        write!(&mut query_string, ",{i}").expect("failed to concatenate ids");
     }
-    result
+    query_string
 }
 
-fn get_expire_from_header(response: &Response<hyper::Body>) -> Duration {
-    let exp = get_header(response, "cache-control").unwrap_or(300);
+/// reads `max-age` from the `cache-control` header, e.g. `public, max-age=300`
+fn get_expire_from_header<B>(response: &Response<B>) -> Duration {
+    let exp = get_header::<String, B>(response, "cache-control")
+        .and_then(|header| {
+            header.split(',').find_map(|directive| {
+                let directive = directive.trim();
+                directive
+                    .strip_prefix("max-age=")
+                    .unwrap_or(directive)
+                    .parse()
+                    .ok()
+            })
+        })
+        .unwrap_or(300);
     Duration::seconds(exp)
 }
 
-fn get_header<T: FromStr>(response: &Response<hyper::Body>, header: &str) -> Option<T> {
+fn get_validators<B>(response: &Response<B>) -> Validators {
+    Validators {
+        etag: get_header(response, "etag"),
+        last_modified: get_header(response, "last-modified"),
+    }
+}
+
+/// the urls of the `Link` header by relation
+///
+/// the header looks like `</v2/items?page=1>; rel=next, </v2/items?page=0>;
+/// rel=first`
+fn get_links<B>(response: &Response<B>) -> HashMap<String, String> {
+    let Some(header) = response.headers().get("link").and_then(|x| x.to_str().ok()) else {
+        return HashMap::new();
+    };
+    header
+        .split(',')
+        .filter_map(|link| {
+            let (url, params) = link.trim().split_once(';')?;
+            let url = url.trim().strip_prefix('<')?.strip_suffix('>')?;
+            let rel = params
+                .split(';')
+                .find_map(|x| x.trim().strip_prefix("rel="))?
+                .trim_matches('"');
+            Some((rel.to_string(), url.to_string()))
+        })
+        .collect()
+}
+
+fn get_header<T: FromStr, B>(response: &Response<B>, header: &str) -> Option<T> {
     response
         .headers()
         .iter()
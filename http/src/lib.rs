@@ -1,9 +1,18 @@
 pub(crate) mod block;
 pub mod cache;
 mod client;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod rate_limit;
+#[cfg(feature = "redis")]
+mod redis_connection;
+pub mod retry;
+mod scheduler;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub use client::*;
 pub use gw2lib_model as model;
+pub use scheduler::Priority;
 use thiserror::Error;
 use tokio::sync::broadcast::error::RecvError;
 
@@ -16,6 +25,8 @@ use crate::{
 pub enum EndpointError {
     #[error("unsupported query type for this endpoint")]
     UnsupportedEndpointQuery,
+    #[error("failed to serialize query parameters: {0}")]
+    InvalidQuery(#[from] serde_urlencoded::ser::Error),
     #[error("endpoint requires authentication")]
     NotAuthenticated,
     #[error("unexpected rate limiting error: {0}")]
@@ -24,15 +35,33 @@ pub enum EndpointError {
     RateLimiterBucketExceeded,
     #[error("connection to gw2 api failed: {0}")]
     RequestFailed(#[from] hyper::Error),
-    #[error("gw2 api returned non success status: {0}")]
-    ApiError(ApiError),
+    #[error("gw2 api returned non success status: {0} ({1})")]
+    ApiError(ApiError, Box<ErrorContext>),
+    #[error("request timed out")]
+    Timeout,
     #[error("failed to retrieve item from already running request: {0}")]
     InflightReceiveFailed(#[from] RecvError),
-    #[error("invalid json response: {0}")]
-    InvalidJsonResponse(#[from] serde_json::Error),
+    #[error("invalid json response at `{path}` ({context}): {source}, near `{excerpt}`")]
+    InvalidJsonResponse {
+        source: serde_json::Error,
+        /// where in the document deserializing failed, e.g. `[3].details.type`
+        path: String,
+        /// the part of the body around the error
+        excerpt: String,
+        context: Box<ErrorContext>,
+    },
+    #[cfg(feature = "testing")]
+    #[error("no recorded response for {0}")]
+    NotRecorded(String),
+    #[cfg(feature = "testing")]
+    #[error("recorded response for {0} is invalid: {1}")]
+    CassetteInvalid(String, hyper::http::Error),
+    #[cfg(feature = "testing")]
+    #[error("failed to write cassette: {0}")]
+    CassetteWrite(std::io::Error),
 }
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub enum ApiError {
     #[error("invalid key")]
     Unauthorized,
@@ -40,8 +69,64 @@ pub enum ApiError {
     MissingGameAccess,
     #[error("too many requests")]
     RateLimited,
+    #[error("not found")]
+    NotFound,
+    /// the requested page is past the last one
+    #[error("page out of range")]
+    PageOutOfRange,
+    /// the endpoint is disabled by arenanet
+    #[error("api not active")]
+    NotActive,
+    /// the api is down, usually for maintenance
+    #[error("api unavailable")]
+    Unavailable,
     #[error("{0}: {1}")]
     Other(hyper::StatusCode, String),
 }
 
+/// the request and response an [`EndpointError`] belongs to
+#[derive(Debug, Clone)]
+pub struct ErrorContext {
+    /// path and query of the request, never containing the api key
+    pub url: String,
+    /// the requested ids, empty for endpoints without ids
+    pub ids: Vec<String>,
+    pub status: hyper::StatusCode,
+    pub headers: hyper::HeaderMap,
+}
+
+impl std::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} from {}", self.status, self.url)
+    }
+}
+
+impl EndpointError {
+    /// the kind of api error, if the api answered with one
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Self::ApiError(e, _) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// the request and response this error belongs to, if it got that far
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Self::ApiError(_, context) | Self::InvalidJsonResponse { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+}
+
 type EndpointResult<T> = Result<T, EndpointError>;
+
+/// replaces every occurrence of `secret` in `text`
+///
+/// Used for error messages and debug output that might echo an api key.
+pub(crate) fn redact(text: &str, secret: Option<&str>) -> String {
+    match secret {
+        Some(secret) if !secret.is_empty() => text.replace(secret, "<redacted>"),
+        _ => text.to_string(),
+    }
+}
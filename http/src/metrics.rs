@@ -0,0 +1,200 @@
+//! counters about the cache and the requests of a client
+//!
+//! Enabled by the `metrics` feature. Every [`crate::Client`] derived from
+//! the same client shares its counters, [`crate::Client::stats`] takes a
+//! snapshot of them.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use dashmap::DashMap;
+use hyper::StatusCode;
+
+/// upper bounds of the latency buckets in milliseconds
+const BUCKETS: [u64; 11] = [5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+/// a snapshot of the counters of a client
+#[derive(Clone, Debug, Default)]
+pub struct ClientStats {
+    /// counters by [`gw2lib_model::Endpoint::NAME`], e.g. `v2/items`
+    pub endpoints: HashMap<String, EndpointStats>,
+    /// time spent waiting for the rate limiter, once per upstream request
+    pub rate_limit_wait: Histogram,
+}
+
+/// the counters of a single endpoint
+#[derive(Clone, Debug, Default)]
+pub struct EndpointStats {
+    /// requests answered by a fresh cache entry
+    pub cache_hits: u64,
+    /// cache lookups without a fresh entry
+    pub cache_misses: u64,
+    /// requests that waited for an identical request already in flight
+    pub inflight_joins: u64,
+    /// requests sent to the api, including retries
+    pub upstream_requests: u64,
+    /// upstream requests that failed without a response
+    pub upstream_failures: u64,
+    /// number of responses by status code
+    pub status_codes: BTreeMap<u16, u64>,
+    /// time until the response body was received
+    pub latency: Histogram,
+}
+
+impl EndpointStats {
+    /// the share of cache lookups answered by the cache, if there were any
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let lookups = self.cache_hits + self.cache_misses;
+        (lookups > 0).then(|| self.cache_hits as f64 / lookups as f64)
+    }
+}
+
+/// durations sorted into buckets
+#[derive(Clone, Debug, Default)]
+pub struct Histogram {
+    /// the number of durations up to and including each upper bound
+    ///
+    /// The last bucket has no upper bound.
+    pub buckets: Vec<(Option<Duration>, u64)>,
+    /// the number of recorded durations
+    pub count: u64,
+    /// the sum of all recorded durations
+    pub sum: Duration,
+}
+
+impl Histogram {
+    /// the average of all recorded durations
+    pub fn mean(&self) -> Option<Duration> {
+        (self.count > 0).then(|| self.sum / self.count as u32)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Metrics {
+    endpoints: DashMap<String, EndpointCounters>,
+    rate_limit_wait: AtomicHistogram,
+}
+
+#[derive(Default)]
+struct EndpointCounters {
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    inflight_joins: AtomicU64,
+    upstream_requests: AtomicU64,
+    upstream_failures: AtomicU64,
+    status_codes: DashMap<u16, u64>,
+    latency: AtomicHistogram,
+}
+
+#[derive(Default)]
+struct AtomicHistogram {
+    buckets: [AtomicU64; BUCKETS.len() + 1],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Metrics {
+    pub(crate) fn cache_lookup(&self, endpoint: &str, hit: bool) {
+        self.with(endpoint, |x| {
+            if hit {
+                x.cache_hits.fetch_add(1, Ordering::Relaxed);
+            } else {
+                x.cache_misses.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+    }
+
+    pub(crate) fn inflight_join(&self, endpoint: &str) {
+        self.with(endpoint, |x| {
+            x.inflight_joins.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+
+    /// records an upstream request, `status` is `None` if it failed
+    pub(crate) fn upstream(&self, endpoint: &str, status: Option<StatusCode>, latency: Duration) {
+        self.with(endpoint, |x| {
+            x.upstream_requests.fetch_add(1, Ordering::Relaxed);
+            match status {
+                Some(status) => *x.status_codes.entry(status.as_u16()).or_default() += 1,
+                None => {
+                    x.upstream_failures.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            x.latency.record(latency);
+        });
+    }
+
+    pub(crate) fn rate_limit_wait(&self, wait: Duration) {
+        self.rate_limit_wait.record(wait);
+    }
+
+    pub(crate) fn snapshot(&self) -> ClientStats {
+        let endpoints = self
+            .endpoints
+            .iter()
+            .map(|x| (x.key().clone(), x.value().snapshot()))
+            .collect();
+        ClientStats {
+            endpoints,
+            rate_limit_wait: self.rate_limit_wait.snapshot(),
+        }
+    }
+
+    fn with(&self, endpoint: &str, f: impl FnOnce(&EndpointCounters)) {
+        let endpoint = endpoint.trim_start_matches('/');
+        if let Some(counters) = self.endpoints.get(endpoint) {
+            return f(&counters);
+        }
+        f(&self.endpoints.entry(endpoint.to_string()).or_default());
+    }
+}
+
+impl EndpointCounters {
+    fn snapshot(&self) -> EndpointStats {
+        EndpointStats {
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+            inflight_joins: self.inflight_joins.load(Ordering::Relaxed),
+            upstream_requests: self.upstream_requests.load(Ordering::Relaxed),
+            upstream_failures: self.upstream_failures.load(Ordering::Relaxed),
+            status_codes: self
+                .status_codes
+                .iter()
+                .map(|x| (*x.key(), *x.value()))
+                .collect(),
+            latency: self.latency.snapshot(),
+        }
+    }
+}
+
+impl AtomicHistogram {
+    fn record(&self, duration: Duration) {
+        let millis = duration.as_millis();
+        let bucket = BUCKETS
+            .iter()
+            .position(|&bound| millis <= bound as u128)
+            .unwrap_or(BUCKETS.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Histogram {
+        let bounds = BUCKETS
+            .iter()
+            .map(|&x| Some(Duration::from_millis(x)))
+            .chain(std::iter::once(None));
+        Histogram {
+            buckets: bounds
+                .zip(&self.buckets)
+                .map(|(bound, count)| (bound, count.load(Ordering::Relaxed)))
+                .collect(),
+            count: self.count.load(Ordering::Relaxed),
+            sum: Duration::from_micros(self.sum_micros.load(Ordering::Relaxed)),
+        }
+    }
+}
@@ -1,29 +1,51 @@
-use std::time::{Duration, Instant};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
-use tokio::sync::Mutex;
 
-use crate::{rate_limit::RateLimiter, EndpointError};
+use crate::{
+    rate_limit::{RateLimiter, RequestContext},
+    EndpointError,
+};
 
 pub struct BucketRateLimiter {
+    global: Mutex<Bucket>,
+    /// burst and refill of the buckets per identifier, if enabled
+    per_identifier: Option<(usize, usize)>,
+    identifiers: Mutex<HashMap<String, Bucket>>,
+}
+
+struct Bucket {
     /// maximum number of requests in burst
     burst: usize,
     /// requests per minute
     refill: usize,
-    time: Mutex<Instant>,
+    time: Instant,
 }
 
 impl BucketRateLimiter {
     /// burst takes the maximum number of requests in burst
     /// refill sets the requests per minute
     pub fn new(burst: usize, refill: usize) -> Self {
-        let now = Instant::now();
-        let max = (60_f64 * 1000_f64 * (burst as f64) / (refill as f64)) as u64;
-        let base = now - Duration::from_millis(max);
         Self {
-            burst,
-            refill,
-            time: base.into(),
+            global: Bucket::new(burst, refill).into(),
+            per_identifier: None,
+            identifiers: Default::default(),
+        }
+    }
+
+    /// additionally limits the requests of every identifier on its own
+    ///
+    /// Requests sending an api key have to fit into both the global bucket
+    /// and the bucket of their [`crate::Client::identifier`], so a single
+    /// heavy key can't use up the whole global bucket.
+    pub fn per_identifier(self, burst: usize, refill: usize) -> Self {
+        Self {
+            per_identifier: Some((burst, refill)),
+            ..self
         }
     }
 }
@@ -36,33 +58,90 @@ impl Default for BucketRateLimiter {
 
 #[async_trait]
 impl RateLimiter for BucketRateLimiter {
-    async fn take(&self, num: usize) -> Result<Duration, EndpointError> {
-        if num > self.burst {
+    async fn take(&self, num: usize, ctx: &RequestContext<'_>) -> Result<Duration, EndpointError> {
+        let now = Instant::now();
+        let mut global = self.global.lock().unwrap();
+        let (identifier, (burst, refill)) = match (ctx.identifier, self.per_identifier) {
+            (Some(identifier), Some(limits)) => (identifier, limits),
+            _ => return global.take(num, now),
+        };
+        if num > global.burst || num > burst {
             return Err(EndpointError::RateLimiterBucketExceeded);
         }
+
+        let mut identifiers = self.identifiers.lock().unwrap();
+        if !identifiers.contains_key(identifier) {
+            // full buckets are the same as new ones
+            identifiers.retain(|_, x| !x.is_full(now));
+        }
+        let bucket = identifiers
+            .entry(identifier.to_string())
+            .or_insert_with(|| Bucket::new(burst, refill));
+        let wait = bucket.take(num, now)?;
+        Ok(wait.max(global.take(num, now)?))
+    }
+
+    async fn penalize(&self, ctx: &RequestContext<'_>) -> Result<(), EndpointError> {
         let now = Instant::now();
-        let max = (60_f64 * 1000_f64 * (self.burst as f64) / (self.refill as f64)) as u64;
-        let base = now - Duration::from_millis(max);
-        let ratio = 60 * 1000 / self.refill as u64;
-        let mut time = self.time.lock().await;
-        if *time < base {
-            *time = base;
+        self.global.lock().unwrap().penalize(now);
+        if let Some(identifier) = ctx.identifier {
+            if let Some(bucket) = self.identifiers.lock().unwrap().get_mut(identifier) {
+                bucket.penalize(now);
+            }
         }
-        *time += Duration::from_millis(ratio * num as u64);
+        Ok(())
+    }
+}
 
-        Ok(time.checked_duration_since(now).unwrap_or(Duration::ZERO))
+impl Bucket {
+    fn new(burst: usize, refill: usize) -> Self {
+        let mut bucket = Self {
+            burst,
+            refill,
+            time: Instant::now(),
+        };
+        bucket.time -= bucket.max();
+        bucket
     }
 
-    async fn penalize(&self) -> Result<(), EndpointError> {
-        let ratio = 60 * 1000 / self.refill as u64;
-        let now = Instant::now();
-        let mut time = self.time.lock().await;
-        if *time < now {
-            *time = now;
+    /// the time it takes to refill the whole bucket
+    fn max(&self) -> Duration {
+        Duration::from_millis(
+            (60_f64 * 1000_f64 * (self.burst as f64) / (self.refill as f64)) as u64,
+        )
+    }
+
+    /// the time it takes to refill a single request
+    fn ratio(&self) -> u64 {
+        60 * 1000 / self.refill as u64
+    }
+
+    fn is_full(&self, now: Instant) -> bool {
+        self.time <= now - self.max()
+    }
+
+    fn take(&mut self, num: usize, now: Instant) -> Result<Duration, EndpointError> {
+        if num > self.burst {
+            return Err(EndpointError::RateLimiterBucketExceeded);
+        }
+        let base = now - self.max();
+        if self.time < base {
+            self.time = base;
+        }
+        self.time += Duration::from_millis(self.ratio() * num as u64);
+
+        Ok(self
+            .time
+            .checked_duration_since(now)
+            .unwrap_or(Duration::ZERO))
+    }
+
+    fn penalize(&mut self, now: Instant) {
+        if self.time < now {
+            self.time = now;
         }
         // the api penalizes us for half a request worth of time when we hit it while
         // rate limited
-        *time += Duration::from_millis(ratio / 2);
-        Ok(())
+        self.time += Duration::from_millis(self.ratio() / 2);
     }
 }
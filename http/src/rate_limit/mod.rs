@@ -13,13 +13,25 @@ pub use noop::NoopRateLimiter;
 pub use self::redis::RedisRateLimiter;
 use crate::EndpointError;
 
+/// what the request a [`RateLimiter`] is asked about is for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RequestContext<'a> {
+    /// the identifier of the api key sent with the request, see
+    /// [`crate::Client::identifier`]
+    ///
+    /// Only set for authenticated endpoints, as the key is not sent otherwise.
+    pub identifier: Option<&'a str>,
+    /// the [`gw2lib_model::Endpoint::NAME`] of the endpoint, e.g. `v2/items`
+    pub endpoint: &'a str,
+}
+
 #[async_trait]
 pub trait RateLimiter {
     /// takes the amount of requests
     /// returns the seconds to wait before executing them
-    async fn take(&self, num: usize) -> Result<Duration, EndpointError>;
+    async fn take(&self, num: usize, ctx: &RequestContext<'_>) -> Result<Duration, EndpointError>;
     /// incurs a penalty, indicating that the rate limit was hit
-    async fn penalize(&self) -> Result<(), EndpointError>;
+    async fn penalize(&self, ctx: &RequestContext<'_>) -> Result<(), EndpointError>;
 }
 
 #[async_trait]
@@ -28,11 +40,11 @@ where
     T: Deref<Target = K> + Sync,
     K: RateLimiter + Sync,
 {
-    async fn take(&self, num: usize) -> Result<Duration, EndpointError> {
-        self.deref().take(num).await
+    async fn take(&self, num: usize, ctx: &RequestContext<'_>) -> Result<Duration, EndpointError> {
+        self.deref().take(num, ctx).await
     }
 
-    async fn penalize(&self) -> Result<(), EndpointError> {
-        self.deref().penalize().await
+    async fn penalize(&self, ctx: &RequestContext<'_>) -> Result<(), EndpointError> {
+        self.deref().penalize(ctx).await
     }
 }
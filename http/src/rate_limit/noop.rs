@@ -2,16 +2,23 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 
-use crate::{rate_limit::RateLimiter, EndpointError};
+use crate::{
+    rate_limit::{RateLimiter, RequestContext},
+    EndpointError,
+};
 
 pub struct NoopRateLimiter;
 #[async_trait]
 impl RateLimiter for NoopRateLimiter {
-    async fn take(&self, _num: usize) -> Result<Duration, EndpointError> {
+    async fn take(
+        &self,
+        _num: usize,
+        _ctx: &RequestContext<'_>,
+    ) -> Result<Duration, EndpointError> {
         Ok(Duration::ZERO)
     }
 
-    async fn penalize(&self) -> Result<(), EndpointError> {
+    async fn penalize(&self, _ctx: &RequestContext<'_>) -> Result<(), EndpointError> {
         Ok(())
     }
 }
@@ -1,65 +1,82 @@
 #!lua name=gw2lib
 
+-- keys are buckets, args are the amount followed by burst and refill of
+-- every bucket
+-- takes from all buckets at once, returning the longest wait
 local function take(keys, args)
-    local amount = args[1]
-    local burst = args[2]
-    local refill = args[3]
-    local key = keys[1]
+    local amount = tonumber(args[1])
 
-    if amount > burst then
-        return redis.error_reply('bucket exhausted')
+    for i = 1, #keys do
+        if amount > tonumber(args[i * 2]) then
+            return redis.error_reply('bucket exhausted')
+        end
     end
 
-    local ratio = math.floor(60000 / refill)
-    local max = math.floor(60000 * burst / refill)
-
     local time = redis.call('TIME')
     local ms = math.ceil(time[1] * 1000 + time[2] / 1000)
-    local base = ms - max
-    local value = redis.call('GET', key)
-    if not value then
-        value = 0
-    end
-    value = tonumber(value)
+    local res = 0
 
-    if base > value then
-        value = base
-    end
+    for i, key in ipairs(keys) do
+        local burst = tonumber(args[i * 2])
+        local refill = tonumber(args[i * 2 + 1])
+
+        local ratio = math.floor(60000 / refill)
+        local max = math.floor(60000 * burst / refill)
 
-    value = value + ratio * amount
+        local base = ms - max
+        local value = redis.call('GET', key)
+        if not value then
+            value = 0
+        end
+        value = tonumber(value)
 
-    redis.call('SET', key, value)
+        if base > value then
+            value = base
+        end
 
-    -- add a buffer of one second
-    local res = value - ms + 1000
-    if res < 0 then
-        res = 0
+        value = value + ratio * amount
+
+        redis.call('SET', key, value)
+        -- a full bucket is the same as a missing one
+        redis.call('PEXPIREAT', key, value + max)
+
+        -- add a buffer of one second
+        local wait = value - ms + 1000
+        if wait > res then
+            res = wait
+        end
     end
+
     return res
 end
 
+-- keys are buckets, args are the refill of every bucket
 local function penalize(keys, args)
-    local refill = args[1]
-    local key = keys[1]
-
-    local ratio = 60000 / refill
-
     local time = redis.call('TIME')
     local ms = math.ceil(time[1] * 1000 + time[2] / 1000)
-    local value = redis.call('GET', key)
-    if not value then
-        value = 0
-    end
-    value = tonumber(value)
 
-    if ms > value then
-        value = ms
-    end
+    for i, key in ipairs(keys) do
+        local refill = tonumber(args[i])
+
+        local ratio = 60000 / refill
 
-    -- the api penalizes for half a request while above the rate limit
-    value = math.floor(value + ratio / 2)
+        local value = redis.call('GET', key)
+        if not value then
+            value = 0
+        end
+        value = tonumber(value)
+
+        if ms > value then
+            value = ms
+        end
+
+        -- the api penalizes for half a request while above the rate limit
+        value = math.floor(value + ratio / 2)
+
+        redis.call('SET', key, value, 'KEEPTTL')
+    end
 
-    return redis.call('SET', key, value)
+    return redis.status_reply('OK')
 end
 
 redis.register_function('ratelimit_take', take)
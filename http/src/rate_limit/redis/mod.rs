@@ -1,9 +1,15 @@
-use std::{future::Future, time::Duration};
+use std::time::Duration;
 
 use async_trait::async_trait;
-use redis::{aio::Connection, Client, RedisError};
+use redis::{Client, RedisError};
 
-use crate::{rate_limit::RateLimiter, EndpointError};
+use crate::{
+    rate_limit::{RateLimiter, RequestContext},
+    redis_connection::SharedConnection,
+    EndpointError,
+};
+
+const KEY: &str = "gw2lib_ratelimit";
 
 #[derive(Debug, Clone)]
 pub struct RedisRateLimiter {
@@ -11,7 +17,9 @@ pub struct RedisRateLimiter {
     burst: usize,
     /// requests per minute
     refill: usize,
-    client: Client,
+    /// burst and refill of the buckets per identifier, if enabled
+    per_identifier: Option<(usize, usize)>,
+    conn: SharedConnection,
 }
 
 impl RedisRateLimiter {
@@ -21,7 +29,7 @@ impl RedisRateLimiter {
     }
 
     #[cfg(not(feature = "blocking"))]
-    pub fn new(client: Client) -> impl Future<Output = Result<Self, RedisError>> {
+    pub fn new(client: Client) -> impl std::future::Future<Output = Result<Self, RedisError>> {
         Self::with_values(client, 300, 300)
     }
 
@@ -35,7 +43,8 @@ impl RedisRateLimiter {
         let this = Self {
             burst,
             refill,
-            client,
+            per_identifier: None,
+            conn: SharedConnection::new(client),
         };
 
         this.setup().await?;
@@ -43,58 +52,86 @@ impl RedisRateLimiter {
         Ok(this)
     }
 
-    async fn setup(&self) -> Result<(), RedisError> {
-        let mut conn = self.connection().await?;
-        redis::cmd("FUNCTION")
-            .arg("LOAD")
-            .arg("REPLACE")
-            .arg(include_str!("lib.lua"))
-            .query_async(&mut conn)
-            .await
+    /// additionally limits the requests of every identifier on its own
+    ///
+    /// Requests sending an api key have to fit into both the global bucket
+    /// and the bucket of their [`crate::Client::identifier`], so a single
+    /// heavy key can't use up the whole global bucket.
+    pub fn per_identifier(self, burst: usize, refill: usize) -> Self {
+        Self {
+            per_identifier: Some((burst, refill)),
+            ..self
+        }
     }
 
-    fn connection(&self) -> impl Future<Output = Result<Connection, RedisError>> + '_ {
-        self.client.get_async_connection()
+    /// the buckets a request has to fit into, with their burst and refill
+    fn buckets(&self, ctx: &RequestContext<'_>) -> Vec<(String, usize, usize)> {
+        let mut buckets = vec![(KEY.to_string(), self.burst, self.refill)];
+        if let (Some(identifier), Some((burst, refill))) = (ctx.identifier, self.per_identifier) {
+            buckets.push((format!("{KEY}_{identifier}"), burst, refill));
+        }
+        buckets
+    }
+
+    async fn setup(&self) -> Result<(), RedisError> {
+        self.conn
+            .run(|mut conn| async move {
+                redis::cmd("FUNCTION")
+                    .arg("LOAD")
+                    .arg("REPLACE")
+                    .arg(include_str!("lib.lua"))
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await
     }
 }
 
 #[async_trait]
 impl RateLimiter for RedisRateLimiter {
-    async fn take(&self, num: usize) -> Result<Duration, EndpointError> {
-        if num > self.burst {
+    async fn take(&self, num: usize, ctx: &RequestContext<'_>) -> Result<Duration, EndpointError> {
+        let buckets = self.buckets(ctx);
+        if buckets.iter().any(|&(_, burst, _)| num > burst) {
             return Err(EndpointError::RateLimiterBucketExceeded);
         }
 
-        let mut conn = self
-            .connection()
-            .await
-            .map_err(|e| EndpointError::RateLimiterCrashed(e.to_string()))?;
-        let wait = redis::cmd("FCALL")
-            .arg("ratelimit_take")
-            .arg(1)
-            .arg("gw2lib_ratelimit")
-            .arg(num)
-            .arg(self.burst)
-            .arg(self.refill)
-            .query_async(&mut conn)
+        let mut cmd = redis::cmd("FCALL");
+        cmd.arg("ratelimit_take").arg(buckets.len());
+        for (key, ..) in &buckets {
+            cmd.arg(key);
+        }
+        cmd.arg(num);
+        for (_, burst, refill) in &buckets {
+            cmd.arg(burst).arg(refill);
+        }
+        let wait = self
+            .conn
+            .run(|mut conn| async move { cmd.query_async(&mut conn).await })
             .await
-            .map_err(|e| EndpointError::RateLimiterCrashed(e.to_string()))?;
+            .map_err(|e| crashed(e, ctx))?;
 
         Ok(Duration::from_millis(wait))
     }
 
-    async fn penalize(&self) -> Result<(), EndpointError> {
-        let mut conn = self
-            .connection()
-            .await
-            .map_err(|e| EndpointError::RateLimiterCrashed(e.to_string()))?;
-        redis::cmd("FCALL")
-            .arg("ratelimit_penalize")
-            .arg(1)
-            .arg("gw2lib_ratelimit")
-            .arg(self.refill)
-            .query_async(&mut conn)
+    async fn penalize(&self, ctx: &RequestContext<'_>) -> Result<(), EndpointError> {
+        let buckets = self.buckets(ctx);
+        let mut cmd = redis::cmd("FCALL");
+        cmd.arg("ratelimit_penalize").arg(buckets.len());
+        for (key, ..) in &buckets {
+            cmd.arg(key);
+        }
+        for (_, _, refill) in &buckets {
+            cmd.arg(refill);
+        }
+        self.conn
+            .run(|mut conn| async move { cmd.query_async(&mut conn).await })
             .await
-            .map_err(|e| EndpointError::RateLimiterCrashed(e.to_string()))
+            .map_err(|e| crashed(e, ctx))
     }
 }
+
+/// the key names of per identifier buckets contain the identifier, which
+/// defaults to the api key
+fn crashed(e: RedisError, ctx: &RequestContext<'_>) -> EndpointError {
+    EndpointError::RateLimiterCrashed(crate::redact(&e.to_string(), ctx.identifier))
+}
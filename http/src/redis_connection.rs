@@ -0,0 +1,64 @@
+use std::{fmt, future::Future, sync::Arc};
+
+use redis::{aio::MultiplexedConnection, Client, RedisError};
+use tokio::sync::Mutex;
+
+/// a multiplexed connection shared by all clones, reconnecting after it broke
+///
+/// Connects lazily on first use. Commands sent concurrently are pipelined
+/// over the same connection instead of opening one per operation.
+#[derive(Clone)]
+pub(crate) struct SharedConnection {
+    client: Client,
+    conn: Arc<Mutex<Option<MultiplexedConnection>>>,
+}
+
+impl SharedConnection {
+    pub(crate) fn new(client: Client) -> Self {
+        Self {
+            client,
+            conn: Default::default(),
+        }
+    }
+
+    /// runs `f` on the shared connection
+    ///
+    /// Drops the connection if `f` fails because it broke, the next call
+    /// connects again.
+    pub(crate) async fn run<T, F, Fut>(&self, f: F) -> Result<T, RedisError>
+    where
+        F: FnOnce(MultiplexedConnection) -> Fut,
+        Fut: Future<Output = Result<T, RedisError>>,
+    {
+        let conn = self.connection().await?;
+        let res = f(conn).await;
+        if let Err(e) = &res {
+            if is_broken(e) {
+                self.conn.lock().await.take();
+            }
+        }
+        res
+    }
+
+    async fn connection(&self) -> Result<MultiplexedConnection, RedisError> {
+        let mut conn = self.conn.lock().await;
+        if let Some(conn) = &*conn {
+            return Ok(conn.clone());
+        }
+        let new = self.client.get_multiplexed_tokio_connection().await?;
+        *conn = Some(new.clone());
+        Ok(new)
+    }
+}
+
+impl fmt::Debug for SharedConnection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedConnection")
+            .field("client", &self.client)
+            .finish_non_exhaustive()
+    }
+}
+
+fn is_broken(e: &RedisError) -> bool {
+    e.is_io_error() || e.is_connection_dropped() || e.is_connection_refusal() || e.is_timeout()
+}
@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use hyper::StatusCode;
+
+/// decides whether and when failed requests are sent again
+///
+/// Retries happen transparently inside the client, so callers of
+/// [`crate::Requester`] and any inflight subscribers waiting for the same
+/// request only ever see the final outcome.
+/// Every attempt waits for the rate limiter like a regular request.
+///
+/// ## Example
+/// ```
+/// use std::time::Duration;
+///
+/// use gw2lib::{retry::RetryPolicy, Client};
+///
+/// let policy = RetryPolicy::new(5)
+///     .base_delay(Duration::from_millis(200))
+///     .max_delay(Duration::from_secs(10));
+/// let client = Client::empty().retry(policy);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// maximum number of attempts, including the first one
+    max_attempts: u32,
+    /// delay before the first retry, doubled for every following retry
+    base_delay: Duration,
+    /// upper bound for a single delay
+    max_delay: Duration,
+    /// response statuses that get retried
+    statuses: Vec<StatusCode>,
+    /// connection errors that get retried
+    errors: fn(&hyper::Error) -> bool,
+}
+
+impl RetryPolicy {
+    /// retries up to `max_attempts - 1` times using the default backoff,
+    /// statuses and errors
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..Default::default()
+        }
+    }
+
+    /// never retries
+    pub fn disabled() -> Self {
+        Self::new(1)
+    }
+
+    /// sets the delay before the first retry
+    ///
+    /// every following retry doubles the delay, up to [`Self::max_delay`]
+    pub fn base_delay(self, base_delay: Duration) -> Self {
+        Self { base_delay, ..self }
+    }
+
+    /// sets the upper bound for a single delay
+    pub fn max_delay(self, max_delay: Duration) -> Self {
+        Self { max_delay, ..self }
+    }
+
+    /// sets the response statuses that get retried
+    ///
+    /// default is 429, 500, 502, 503 and 504
+    pub fn statuses(self, statuses: impl Into<Vec<StatusCode>>) -> Self {
+        Self {
+            statuses: statuses.into(),
+            ..self
+        }
+    }
+
+    /// sets which connection errors get retried
+    ///
+    /// default is [`is_transient`]
+    pub fn errors(self, errors: fn(&hyper::Error) -> bool) -> Self {
+        Self { errors, ..self }
+    }
+
+    pub(crate) fn should_retry_status(&self, attempt: u32, status: StatusCode) -> bool {
+        attempt < self.max_attempts && self.statuses.contains(&status)
+    }
+
+    pub(crate) fn should_retry_error(&self, attempt: u32, error: &hyper::Error) -> bool {
+        attempt < self.max_attempts && (self.errors)(error)
+    }
+
+    /// timeouts of [`crate::Client::timeout`] are always transient
+    pub(crate) fn should_retry_timeout(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// returns the time to wait after the given failed attempt
+    ///
+    /// uses exponential backoff with equal jitter, never waiting less than
+    /// the `retry-after` the api asked for
+    pub(crate) fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        let half = exp / 2;
+        let jitter = Duration::from_nanos(fastrand::u64(0..=half.as_nanos() as u64));
+        let delay = half + jitter;
+        match retry_after {
+            Some(retry_after) => delay.max(retry_after.min(self.max_delay)),
+            None => delay,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            errors: is_transient,
+        }
+    }
+}
+
+/// returns true for errors that are likely to go away on their own, like
+/// failing to connect or the connection being closed mid response
+pub fn is_transient(error: &hyper::Error) -> bool {
+    error.is_connect() || error.is_closed() || error.is_incomplete_message() || error.is_timeout()
+}
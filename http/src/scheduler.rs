@@ -0,0 +1,109 @@
+//! ordering of requests waiting for the rate limiter
+//!
+//! Every identifier has its own lane in the [`Scheduler`]. Only one request
+//! per lane at a time waits for the rate limiter, the others queue behind it
+//! and are picked from the highest [`Priority`] with waiting requests. A
+//! throttled identifier therefore never holds up requests of other
+//! identifiers.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::oneshot;
+
+/// how urgent a request is, see [`crate::Requester::priority`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// background work like crawls with `all` or cache refreshes
+    Low,
+    #[default]
+    Normal,
+    /// latency sensitive requests, e.g. answering a user
+    High,
+}
+
+impl Priority {
+    const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// hands out the right to wait for the rate limiter, shared by all clones of a
+/// client
+#[derive(Default)]
+pub(crate) struct Scheduler {
+    /// the lanes of identifiers with a request waiting for the rate limiter
+    lanes: Mutex<HashMap<Option<String>, Lane>>,
+}
+
+/// the waiting requests of one identifier, by priority
+#[derive(Default)]
+struct Lane {
+    queues: [VecDeque<oneshot::Sender<()>>; 3],
+}
+
+/// allows its holder to wait for the rate limiter, passing the turn on to the
+/// next request of the same identifier when dropped
+pub(crate) struct Permit {
+    scheduler: Arc<Scheduler>,
+    identifier: Option<String>,
+}
+
+impl Scheduler {
+    /// waits for the turn of a request of `identifier`
+    pub(crate) async fn acquire(
+        self: &Arc<Self>,
+        priority: Priority,
+        identifier: Option<&str>,
+    ) -> Permit {
+        let identifier = identifier.map(ToString::to_string);
+        let rx = {
+            let mut lanes = self.lanes.lock().unwrap();
+            match lanes.get_mut(&identifier) {
+                None => {
+                    lanes.insert(identifier.clone(), Lane::default());
+                    None
+                }
+                Some(lane) => {
+                    let (tx, rx) = oneshot::channel();
+                    lane.queues[priority.index()].push_back(tx);
+                    Some(rx)
+                }
+            }
+        };
+        if let Some(rx) = rx {
+            // the sender is only dropped together with the scheduler
+            let _ = rx.await;
+        }
+        Permit {
+            scheduler: self.clone(),
+            identifier,
+        }
+    }
+
+    fn release(&self, identifier: &Option<String>) {
+        let mut lanes = self.lanes.lock().unwrap();
+        let Some(lane) = lanes.get_mut(identifier) else {
+            return;
+        };
+        for priority in Priority::ALL {
+            while let Some(tx) = lane.queues[priority.index()].pop_front() {
+                // skip requests that got cancelled while waiting
+                if tx.send(()).is_ok() {
+                    return;
+                }
+            }
+        }
+        lanes.remove(identifier);
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.scheduler.release(&self.identifier);
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{self, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use hyper::{body::Bytes, Response, Uri};
+use serde::{Deserialize, Serialize};
+
+use crate::{EndpointError, EndpointResult};
+
+/// recorded api responses for deterministic tests
+///
+/// A cassette either records every response a client receives into a json
+/// file, or serves a previously recorded file back without any network
+/// access.
+/// Requests are matched on their path and query. Api keys are sent in a
+/// header and never recorded, so a cassette recorded with one api key replays
+/// with any other key and any host.
+/// Repeated requests for the same uri are replayed in the order they were
+/// recorded, reusing the last response once they run out.
+///
+/// Clients only record the final response of a request, after all retries.
+/// Share one cassette between clients with [`crate::Client::cassette`], as
+/// every recording cassette overwrites its file.
+///
+/// ## Example
+/// ```no_run
+/// use gw2lib::{model::misc::build::Build, Client, Requester};
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// // once, against the real api
+/// let client = Client::default().record("tests/cassettes/build.json");
+/// # #[cfg(not(feature = "blocking"))]
+/// let _: Build = client.get().await?;
+/// # #[cfg(feature = "blocking")]
+/// # let _: Build = client.get()?;
+///
+/// // in every test run afterwards
+/// let client = Client::default().replay("tests/cassettes/build.json")?;
+/// # #[cfg(not(feature = "blocking"))]
+/// let _: Build = client.get().await?;
+/// # #[cfg(feature = "blocking")]
+/// # let _: Build = client.get()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: Mode,
+    interactions: Mutex<Vec<Interaction>>,
+    cursors: Mutex<HashMap<String, usize>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    uri: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Cassette {
+    /// records into a new cassette at `path`, replacing any existing file
+    ///
+    /// Every response is appended to the file as it arrives.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: Mode::Record,
+            interactions: Default::default(),
+            cursors: Default::default(),
+        }
+    }
+
+    /// loads the cassette at `path` for replaying
+    pub fn replay(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let interactions = serde_json::from_slice(&fs::read(&path)?)?;
+        Ok(Self {
+            path,
+            mode: Mode::Replay,
+            interactions: Mutex::new(interactions),
+            cursors: Default::default(),
+        })
+    }
+
+    pub(crate) fn is_replaying(&self) -> bool {
+        self.mode == Mode::Replay
+    }
+
+    /// returns the next recorded response for `uri`
+    pub(crate) fn play(&self, uri: &Uri) -> EndpointResult<Response<Bytes>> {
+        let uri = redact(uri);
+        let interactions = self.interactions.lock().unwrap();
+        let matching: Vec<&Interaction> = interactions
+            .iter()
+            .filter(|i| without_access_token(&i.uri) == uri)
+            .collect();
+        let mut cursors = self.cursors.lock().unwrap();
+        let cursor = cursors.entry(uri.clone()).or_default();
+        let interaction = matching
+            .get(*cursor)
+            .or_else(|| matching.last())
+            .ok_or_else(|| EndpointError::NotRecorded(uri.clone()))?;
+        *cursor += 1;
+
+        let mut response = Response::builder().status(interaction.status);
+        for (name, value) in &interaction.headers {
+            response = response.header(name, value);
+        }
+        response
+            .body(Bytes::from(interaction.body.clone()))
+            .map_err(|e| EndpointError::CassetteInvalid(uri, e))
+    }
+
+    /// appends `response` for `uri` to the cassette on disk
+    pub(crate) fn store(&self, uri: &Uri, response: &Response<Bytes>) -> EndpointResult<()> {
+        let interaction = Interaction {
+            uri: redact(uri),
+            status: response.status().as_u16(),
+            headers: response
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string(),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )
+                })
+                .collect(),
+            body: String::from_utf8_lossy(response.body()).into_owned(),
+        };
+
+        let mut interactions = self.interactions.lock().unwrap();
+        let json = serde_json::to_string_pretty(&interaction)
+            .map_err(|e| EndpointError::CassetteWrite(e.into()))?;
+        let res = if interactions.is_empty() {
+            fs::write(&self.path, format!("[\n{json}\n]"))
+        } else {
+            append(&self.path, &json)
+        };
+        interactions.push(interaction);
+        res.map_err(EndpointError::CassetteWrite)
+    }
+}
+
+/// adds `json` to the array in the file at `path`, replacing its closing
+/// `\n]`
+fn append(path: &Path, json: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    file.seek(SeekFrom::End(-2))?;
+    file.write_all(format!(",\n{json}\n]").as_bytes())
+}
+
+/// path and query of `uri`
+fn redact(uri: &Uri) -> String {
+    let path_and_query = uri.path_and_query().map(|x| x.as_str()).unwrap_or("/");
+    without_access_token(path_and_query)
+}
+
+/// drops the `access_token` parameter, which older versions sent in the query
+/// and recorded as `access_token=REDACTED`
+fn without_access_token(path_and_query: &str) -> String {
+    let Some((path, query)) = path_and_query.split_once('?') else {
+        return path_and_query.to_string();
+    };
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.starts_with("access_token="))
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("{path}?{query}")
+}
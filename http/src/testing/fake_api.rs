@@ -0,0 +1,566 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet, VecDeque},
+    convert::Infallible,
+    hash::{Hash, Hasher},
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use gw2lib_model::{
+    BulkEndpoint, Endpoint, FixedEndpoint, Language, PagedEndpoint, ParentBulkEndpoint,
+    ParentEndpoint, QueryEndpoint,
+};
+use hyper::{
+    header::{
+        HeaderName, HeaderValue, AUTHORIZATION, CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH,
+    },
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+/// a local stand-in for the gw2 api
+///
+/// Serves json fixtures over plain http on a random local port, so a client
+/// pointed at it with [`crate::Client::host_http`] goes through the full
+/// request pipeline without network access.
+///
+/// Endpoints with ids understand `ids=`, `ids=all`, `id=`, `<url>/<id>`,
+/// `page=`/`page_size=` and the id listing, and answer with the same status
+/// codes, error texts and `x-result-*`/`x-page-*`/`cache-control` headers as
+/// the real api.
+/// Successful responses carry an `etag` and are answered with
+/// `304 Not Modified` when it matches `if-none-match`.
+/// Authenticated endpoints only answer to keys registered with
+/// [`FakeApi::api_key`], sent either as `Authorization: Bearer` header or as
+/// `access_token` query parameter.
+///
+/// The server shuts down when this is dropped.
+///
+/// ## Example
+/// ```no_run
+/// use gw2lib::{model::misc::build::Build, testing::FakeApi, Client, Requester};
+///
+/// # async fn run() -> Result<(), gw2lib::EndpointError> {
+/// let api = FakeApi::start();
+/// api.fixed(&Build { id: 115267 });
+///
+/// let client = Client::default().host_http(api.url());
+/// # #[cfg(not(feature = "blocking"))]
+/// let build: Build = client.get().await?;
+/// # #[cfg(feature = "blocking")]
+/// # let build: Build = client.get()?;
+/// assert_eq!(build.id, 115267);
+/// # Ok(())
+/// # }
+/// ```
+pub struct FakeApi {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// a canned response returned once by [`FakeApi::respond_with`]
+#[derive(Clone, Debug)]
+pub struct FakeResponse {
+    status: StatusCode,
+    headers: Vec<(String, String)>,
+    body: String,
+    delay: Duration,
+}
+
+/// how long to wait before sending a response
+#[derive(Clone, Copy)]
+struct Delay(Duration);
+
+struct State {
+    fixtures: HashMap<String, Fixture>,
+    scripts: HashMap<String, VecDeque<FakeResponse>>,
+    keys: HashSet<String>,
+    max_age: u64,
+    requests: Vec<String>,
+}
+
+#[derive(Default)]
+struct Fixture {
+    authenticated: bool,
+    /// the query parameter taking multiple ids, `ids` if not set
+    ids: Option<&'static str>,
+    data: HashMap<Option<Language>, Data>,
+}
+
+enum Data {
+    Fixed(Value),
+    Bulk(Vec<(String, Value)>),
+    Paged(Vec<Value>),
+    /// values and the query parameters they answer to
+    Query(Vec<(HashMap<String, String>, Value)>),
+}
+
+impl FakeApi {
+    /// starts the server on a random local port
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind fake api");
+        listener
+            .set_nonblocking(true)
+            .expect("failed to configure fake api listener");
+        let addr = listener.local_addr().expect("fake api has no address");
+        let state = Arc::new(Mutex::new(State {
+            fixtures: Default::default(),
+            scripts: Default::default(),
+            keys: Default::default(),
+            max_age: 300,
+            requests: Default::default(),
+        }));
+        let (shutdown, rx) = oneshot::channel::<()>();
+
+        let server_state = state.clone();
+        let thread = std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("build fake api runtime");
+            rt.block_on(async move {
+                let make_service = make_service_fn(move |_| {
+                    let state = server_state.clone();
+                    async move {
+                        Ok::<_, Infallible>(service_fn(move |request| {
+                            let response = handle(&state, request);
+                            async move {
+                                if let Some(Delay(delay)) = response.extensions().get() {
+                                    tokio::time::sleep(*delay).await;
+                                }
+                                Ok::<_, Infallible>(response)
+                            }
+                        }))
+                    }
+                });
+                let server = Server::from_tcp(listener)
+                    .expect("failed to start fake api")
+                    .serve(make_service);
+                // dropping the runtime afterwards closes all open connections
+                futures::future::select(server, rx).await;
+            });
+        });
+
+        Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        }
+    }
+
+    /// the host to pass to [`crate::Client::host_http`]
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// serves `value` for the fixed endpoint `T`
+    pub fn fixed<T: FixedEndpoint + Serialize>(&self, value: &T) {
+        self.insert::<T>(None, Data::Fixed(to_value(value)));
+    }
+
+    /// serves `value` for the fixed endpoint `T` when requested in `lang`
+    pub fn fixed_in<T: FixedEndpoint + Serialize>(&self, lang: Language, value: &T) {
+        self.insert::<T>(Some(lang), Data::Fixed(to_value(value)));
+    }
+
+    /// serves `entries` for the bulk endpoint `T`, replacing previous entries
+    pub fn bulk<T: BulkEndpoint + Serialize>(&self, entries: &[T]) {
+        self.insert::<T>(None, Data::Bulk(bulk_entries(entries)));
+    }
+
+    /// serves `entries` for the bulk endpoint `T` when requested in `lang`
+    pub fn bulk_in<T: BulkEndpoint + Serialize>(&self, lang: Language, entries: &[T]) {
+        self.insert::<T>(Some(lang), Data::Bulk(bulk_entries(entries)));
+    }
+
+    /// serves `entries` for the paged endpoint `T`, which is always answered
+    /// with a page
+    ///
+    /// use [`FakeApi::bulk`] for endpoints with ids
+    pub fn paged<T: PagedEndpoint + Serialize>(&self, entries: &[T]) {
+        self.insert::<T>(None, Data::Paged(entries.iter().map(to_value).collect()));
+    }
+
+    /// serves `value` for the query endpoint `T` when requested with `params`
+    ///
+    /// requests with other parameters are answered with `404 Not Found`
+    pub fn query<T: QueryEndpoint + Serialize>(&self, params: &T::Query, value: &T) {
+        let params = serde_urlencoded::to_string(params).expect("query is not serializable");
+        let entry = (parse_query(&params), to_value(value));
+        let mut state = self.state.lock().unwrap();
+        let fixture = state.fixtures.entry(T::URL.to_string()).or_default();
+        fixture.authenticated = T::AUTHENTICATED;
+        match fixture.data.entry(None).or_insert(Data::Query(Vec::new())) {
+            Data::Query(entries) => entries.push(entry),
+            data => *data = Data::Query(vec![entry]),
+        }
+    }
+
+    /// serves `value` for the parent endpoint `T` below `parent`
+    pub fn scoped<T: ParentEndpoint + Serialize>(&self, parent: &T::ParentId, value: &T) {
+        let mut state = self.state.lock().unwrap();
+        let url = T::parent_url(&T::format_parent(parent));
+        let fixture = state.fixtures.entry(url).or_default();
+        fixture.authenticated = T::AUTHENTICATED;
+        fixture.data.insert(None, Data::Fixed(to_value(value)));
+    }
+
+    /// serves `entries` for the parent endpoint `T` below `parent`, which
+    /// understands the same requests as [`FakeApi::bulk`]
+    pub fn scoped_bulk<T: ParentBulkEndpoint + Serialize>(
+        &self,
+        parent: &T::ParentId,
+        entries: &[T],
+    ) {
+        let entries = entries
+            .iter()
+            .map(|x| (x.id().to_string(), to_value(x)))
+            .collect();
+        let mut state = self.state.lock().unwrap();
+        let url = T::parent_url(&T::format_parent(parent));
+        let fixture = state.fixtures.entry(url).or_default();
+        fixture.authenticated = T::AUTHENTICATED;
+        fixture.ids = Some(T::IDS);
+        fixture.data.insert(None, Data::Bulk(entries));
+    }
+
+    /// serves `value` at the exact `path`, e.g. `v2/characters/Name/core`
+    ///
+    /// the path is neither authenticated nor localized
+    pub fn raw(&self, path: impl Into<String>, value: impl Serialize) {
+        let mut state = self.state.lock().unwrap();
+        let fixture = state.fixtures.entry(path.into()).or_default();
+        fixture.data.insert(None, Data::Fixed(to_value(&value)));
+    }
+
+    /// accepts `key` as access token for authenticated endpoints
+    pub fn api_key(&self, key: impl Into<String>) {
+        self.state.lock().unwrap().keys.insert(key.into());
+    }
+
+    /// sets the `max-age` sent in the `cache-control` header
+    ///
+    /// default is 300 seconds
+    pub fn max_age(&self, seconds: u64) {
+        self.state.lock().unwrap().max_age = seconds;
+    }
+
+    /// answers the next request to `url` (or any id below it) with
+    /// `response` instead of the fixture
+    ///
+    /// calling this multiple times queues the responses in order
+    pub fn respond_with(&self, url: impl Into<String>, response: FakeResponse) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .scripts
+            .entry(url.into())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// every path and query received so far, in order
+    ///
+    /// e.g. `/v2/items?v=2021-01-11T00:00:00.000Z&ids=1,2`
+    pub fn requests(&self) -> Vec<String> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    fn insert<T: Endpoint>(&self, lang: Option<Language>, data: Data) {
+        let mut state = self.state.lock().unwrap();
+        let fixture = state.fixtures.entry(T::URL.to_string()).or_default();
+        fixture.authenticated = T::AUTHENTICATED;
+        fixture.data.insert(lang, data);
+    }
+}
+
+impl Drop for FakeApi {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl FakeResponse {
+    /// an empty json object with the given status
+    pub fn new(status: StatusCode) -> Self {
+        Self::json(status, json!({}))
+    }
+
+    /// an error in the format of the api: `{"text": "<text>"}`
+    pub fn error(status: StatusCode, text: impl Into<String>) -> Self {
+        Self::json(status, json!({ "text": text.into() }))
+    }
+
+    /// `body` serialized as json
+    pub fn json(status: StatusCode, body: impl Serialize) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: to_value(&body).to_string(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// adds a response header
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// waits before sending the response, e.g. to let clients time out
+    pub fn delay(self, delay: Duration) -> Self {
+        Self { delay, ..self }
+    }
+
+    fn into_response(self) -> Response<Body> {
+        let mut response = Response::new(Body::from(self.body));
+        *response.status_mut() = self.status;
+        if !self.delay.is_zero() {
+            response.extensions_mut().insert(Delay(self.delay));
+        }
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+        response
+    }
+}
+
+fn handle(state: &Mutex<State>, request: Request<Body>) -> Response<Body> {
+    let mut state = state.lock().unwrap();
+    let uri = request.uri();
+    state.requests.push(
+        uri.path_and_query()
+            .map(|x| x.to_string())
+            .unwrap_or_default(),
+    );
+
+    let path = uri.path().trim_start_matches('/').to_string();
+    let query = parse_query(uri.query().unwrap_or_default());
+    let (url, id) = if state.fixtures.contains_key(&path) || state.scripts.contains_key(&path) {
+        (path, None)
+    } else {
+        match path.rsplit_once('/') {
+            Some((url, id)) => (
+                url.to_string(),
+                Some(
+                    urlencoding::decode(id)
+                        .map(|x| x.into_owned())
+                        .unwrap_or_default(),
+                ),
+            ),
+            None => (path, None),
+        }
+    };
+
+    if let Some(response) = state.scripts.get_mut(&url).and_then(|x| x.pop_front()) {
+        return response.into_response();
+    }
+
+    let fixture = match state.fixtures.get(&url) {
+        Some(fixture) => fixture,
+        None => return FakeResponse::error(StatusCode::NOT_FOUND, "not found").into_response(),
+    };
+
+    if fixture.authenticated {
+        let bearer = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|x| x.to_str().ok())
+            .and_then(|x| x.strip_prefix("Bearer "));
+        let authorized = bearer
+            .or(query.get("access_token").map(String::as_str))
+            .is_some_and(|key| state.keys.contains(key));
+        if !authorized {
+            return FakeResponse::error(StatusCode::UNAUTHORIZED, "Invalid access token")
+                .into_response();
+        }
+    }
+
+    let lang = query.get("lang").map(|x| Language::from(x.as_str()));
+    let data = fixture
+        .data
+        .get(&lang)
+        .or_else(|| fixture.data.get(&None))
+        .or_else(|| fixture.data.values().next());
+
+    let response = match data {
+        Some(Data::Fixed(value)) => FakeResponse::json(StatusCode::OK, value),
+        Some(Data::Bulk(entries)) => bulk(&url, entries, id, &query, fixture.ids.unwrap_or("ids")),
+        Some(Data::Paged(entries)) => page(&url, entries.iter().collect(), &query),
+        Some(Data::Query(entries)) => entries
+            .iter()
+            .find(|(params, _)| params.iter().all(|(k, v)| query.get(k) == Some(v)))
+            .map_or_else(
+                || FakeResponse::error(StatusCode::NOT_FOUND, "not found"),
+                |(_, value)| FakeResponse::json(StatusCode::OK, value),
+            ),
+        None => FakeResponse::error(StatusCode::NOT_FOUND, "not found"),
+    };
+    let mut response = response.header(
+        CACHE_CONTROL.as_str(),
+        format!("public, max-age={}", state.max_age),
+    );
+    if response.status == StatusCode::OK {
+        let etag = {
+            let mut hasher = DefaultHasher::new();
+            response.body.hash(&mut hasher);
+            format!("\"{:x}\"", hasher.finish())
+        };
+        let unchanged = request
+            .headers()
+            .get(IF_NONE_MATCH)
+            .is_some_and(|x| x.as_bytes() == etag.as_bytes());
+        if unchanged {
+            response.status = StatusCode::NOT_MODIFIED;
+            response.body = String::new();
+        }
+        response = response.header(ETAG.as_str(), etag);
+    }
+    response.into_response()
+}
+
+fn bulk(
+    url: &str,
+    entries: &[(String, Value)],
+    id: Option<String>,
+    query: &HashMap<String, String>,
+    ids_param: &str,
+) -> FakeResponse {
+    let find = |id: &str| entries.iter().find(|(x, _)| x == id).map(|(_, v)| v);
+
+    if let Some(id) = id.as_ref().or_else(|| query.get("id")) {
+        return match find(id) {
+            Some(value) => FakeResponse::json(StatusCode::OK, value),
+            None => FakeResponse::error(StatusCode::NOT_FOUND, "no such id"),
+        };
+    }
+
+    if let Some(ids) = query.get(ids_param) {
+        let found: Vec<&Value> = if ids == "all" {
+            entries.iter().map(|(_, v)| v).collect()
+        } else {
+            ids.split(',').filter_map(find).collect()
+        };
+        let requested = ids.split(',').count();
+        return match found.len() {
+            0 if ids != "all" => {
+                FakeResponse::error(StatusCode::NOT_FOUND, "all ids provided are invalid")
+            }
+            n if ids != "all" && n < requested => {
+                FakeResponse::json(StatusCode::PARTIAL_CONTENT, &found)
+            }
+            _ => FakeResponse::json(StatusCode::OK, &found),
+        }
+        .header("x-result-total", entries.len().to_string())
+        .header("x-result-count", found.len().to_string());
+    }
+
+    if query.contains_key("page") || query.contains_key("page_size") {
+        return page(url, entries.iter().map(|(_, v)| v).collect(), query);
+    }
+
+    let ids: Vec<Value> = entries.iter().map(|(id, _)| id_value(id)).collect();
+    FakeResponse::json(StatusCode::OK, ids)
+        .header("x-result-total", entries.len().to_string())
+        .header("x-result-count", entries.len().to_string())
+}
+
+fn page(url: &str, entries: Vec<&Value>, query: &HashMap<String, String>) -> FakeResponse {
+    let page_size = match query.get("page_size").map(|x| x.parse::<usize>()) {
+        None => 50,
+        Some(Ok(size)) if (1..=200).contains(&size) => size,
+        Some(_) => {
+            return FakeResponse::error(
+                StatusCode::BAD_REQUEST,
+                "page_size out of range. Use page_size values 1 - 200.",
+            )
+        }
+    };
+    let pages = entries.len().div_ceil(page_size).max(1);
+    let page = match query.get("page").map(|x| x.parse::<usize>()) {
+        None => 0,
+        Some(Ok(page)) if page < pages => page,
+        Some(_) => {
+            return FakeResponse::error(
+                StatusCode::BAD_REQUEST,
+                format!("page out of range. Use page values 0 - {}.", pages - 1),
+            )
+        }
+    };
+
+    let found: Vec<&Value> = entries
+        .iter()
+        .skip(page * page_size)
+        .take(page_size)
+        .copied()
+        .collect();
+
+    let link = |page: usize| format!("</{url}?page={page}&page_size={page_size}>");
+    let mut links = Vec::with_capacity(5);
+    if page > 0 {
+        links.push(format!("{}; rel=previous", link(page - 1)));
+    }
+    if page + 1 < pages {
+        links.push(format!("{}; rel=next", link(page + 1)));
+    }
+    links.push(format!("{}; rel=self", link(page)));
+    links.push(format!("{}; rel=first", link(0)));
+    links.push(format!("{}; rel=last", link(pages - 1)));
+
+    FakeResponse::json(StatusCode::OK, &found)
+        .header("x-page-total", pages.to_string())
+        .header("x-page-size", page_size.to_string())
+        .header("x-result-total", entries.len().to_string())
+        .header("x-result-count", found.len().to_string())
+        .header("link", links.join(", "))
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|x| x.split_once('='))
+        .map(|(k, v)| {
+            let v = urlencoding::decode(v)
+                .map(|x| x.into_owned())
+                .unwrap_or_else(|_| v.to_string());
+            (k.to_string(), v)
+        })
+        .collect()
+}
+
+fn bulk_entries<T: BulkEndpoint + Serialize>(entries: &[T]) -> Vec<(String, Value)> {
+    entries
+        .iter()
+        .map(|x| (x.id().to_string(), to_value(x)))
+        .collect()
+}
+
+/// ids are listed as numbers where possible, like the api does
+fn id_value(id: &str) -> Value {
+    id.parse::<u64>()
+        .map(Value::from)
+        .unwrap_or_else(|_| Value::from(id))
+}
+
+fn to_value(value: &impl Serialize) -> Value {
+    serde_json::to_value(value).expect("fixture is not serializable")
+}
@@ -0,0 +1,9 @@
+//! utilities for testing code built on gw2lib without reaching the real api
+//!
+//! Only available with the `testing` feature.
+
+mod cassette;
+mod fake_api;
+
+pub use cassette::Cassette;
+pub use fake_api::{FakeApi, FakeResponse};
@@ -0,0 +1,133 @@
+#![cfg(all(feature = "blocking", feature = "testing"))]
+
+use std::path::PathBuf;
+
+use gw2lib::{
+    model::{
+        authenticated::account::materials::{AccountMaterial, AccountMaterials},
+        misc::build::Build,
+    },
+    testing::FakeApi,
+    Client, EndpointError, Requester,
+};
+
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("gw2lib_{}_{name}.json", std::process::id()))
+}
+
+fn materials() -> AccountMaterials {
+    vec![AccountMaterial {
+        id: 19721,
+        category: 5,
+        count: 250,
+    }]
+}
+
+#[test]
+fn record_and_replay() {
+    let path = cassette_path("record_and_replay");
+    let host = {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 115267 });
+        let client = Client::empty().host_http(api.url()).record(&path);
+        let _: Build = client.get().unwrap();
+        api.url()
+    };
+
+    let client = Client::empty().host_http(host).replay(&path).unwrap();
+    let build: Build = client.get().unwrap();
+    assert_eq!(build.id, 115267);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn redacts_api_key() {
+    let path = cassette_path("redacts_api_key");
+    {
+        let api = FakeApi::start();
+        api.api_key("secret-key");
+        api.fixed::<AccountMaterials>(&materials());
+        let client = Client::empty()
+            .host_http(api.url())
+            .api_key("secret-key")
+            .record(&path);
+        let _: AccountMaterials = client.get().unwrap();
+    }
+
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    assert!(!cassette.contains("secret-key"));
+
+    let client = Client::empty()
+        .host_http("http://127.0.0.1:1")
+        .api_key("other-key")
+        .replay(&path)
+        .unwrap();
+    let replayed: AccountMaterials = client.get().unwrap();
+    assert_eq!(replayed[0].count, 250);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn replays_in_order() {
+    let path = cassette_path("replays_in_order");
+    {
+        let api = FakeApi::start();
+        let client = Client::empty().host_http(api.url()).record(&path);
+        api.fixed(&Build { id: 1 });
+        let _: Build = client.get().unwrap();
+        api.fixed(&Build { id: 2 });
+        let _: Build = client.get().unwrap();
+    }
+
+    let client = Client::empty()
+        .host_http("http://127.0.0.1:1")
+        .replay(&path)
+        .unwrap();
+    let ids: Vec<u64> = (0..3).map(|_| client.get::<Build>().unwrap().id).collect();
+    assert_eq!(ids, vec![1, 2, 2]);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn missing_response() {
+    let path = cassette_path("missing_response");
+    std::fs::write(&path, "[]").unwrap();
+
+    let client = Client::empty()
+        .host_http("http://127.0.0.1:1")
+        .replay(&path)
+        .unwrap();
+    let res: Result<Build, _> = client.get();
+    assert!(matches!(res, Err(EndpointError::NotRecorded(_))));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn invalid_response() {
+    let path = cassette_path("invalid_response");
+    {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        let client = Client::empty().host_http(api.url()).record(&path);
+        let _: Build = client.get().unwrap();
+    }
+    let cassette = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(
+        &path,
+        cassette.replace("\"status\": 200", "\"status\": 1000"),
+    )
+    .unwrap();
+
+    let client = Client::empty()
+        .host_http("http://127.0.0.1:1")
+        .replay(&path)
+        .unwrap();
+    let res: Result<Build, _> = client.get();
+    assert!(matches!(res, Err(EndpointError::CassetteInvalid(..))));
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -2,7 +2,8 @@
 
 use gw2lib::{
     model::authenticated::characters::{
-        Backstory, Character, CharacterId, Core, Crafting, Equipment, Inventory, Recipes, Training,
+        Backstory, BuildTab, Character, CharacterId, Core, Crafting, Equipment, EquipmentTab,
+        Inventory, Recipes, Training,
     },
     Requester,
 };
@@ -70,3 +71,25 @@ fn training() {
     let client = setup::setup();
     let _: Training = client.single(character_name()).unwrap();
 }
+
+#[test]
+fn core_scoped() {
+    let client = setup::setup();
+    let _: Core = client.scoped(character_name()).unwrap();
+}
+
+#[test]
+fn buildtabs() {
+    let client = setup::setup();
+    let tabs = client
+        .scoped_ids::<BuildTab, _, _>(character_name())
+        .unwrap();
+    let _: BuildTab = client.scoped_single(character_name(), tabs[0]).unwrap();
+    let _: Vec<BuildTab> = client.scoped_all(character_name()).unwrap();
+}
+
+#[test]
+fn equipmenttabs() {
+    let client = setup::setup();
+    let _: Vec<EquipmentTab> = client.scoped_all(character_name()).unwrap();
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "redis")]
+
+use gw2lib::{
+    cache::{Codec, Compressed, Compression, Json},
+    model::misc::worlds::{PopulationLevel, World},
+};
+
+/// run length encoding, good enough to tell whether compression was applied
+struct Rle;
+
+impl Compression for Rle {
+    fn compress(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        let mut out = Vec::new();
+        for chunk in bytes.chunk_by(|a, b| a == b) {
+            for part in chunk.chunks(255) {
+                out.extend([part.len() as u8, part[0]]);
+            }
+        }
+        Some(out)
+    }
+
+    fn decompress(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        if !bytes.len().is_multiple_of(2) {
+            return None;
+        }
+        Some(
+            bytes
+                .chunks(2)
+                .flat_map(|x| std::iter::repeat_n(x[1], x[0] as usize))
+                .collect(),
+        )
+    }
+}
+
+fn world() -> World {
+    World {
+        id: 1001,
+        name: format!("World{}", " ".repeat(100)),
+        population: PopulationLevel::High,
+    }
+}
+
+#[test]
+fn json() {
+    let bytes = Json.encode(&world()).unwrap();
+    assert!(bytes.starts_with(b"{\"id\":1001,"));
+    let world: World = Json.decode(&bytes).unwrap();
+    assert_eq!(world.id, 1001);
+}
+
+#[test]
+fn compressed() {
+    let codec = Compressed::new(Json, Rle);
+    let bytes = codec.encode(&world()).unwrap();
+    assert!(bytes.len() < Json.encode(&world()).unwrap().len());
+
+    let world: World = codec.decode(&bytes).unwrap();
+    assert_eq!(world.name, self::world().name);
+    assert!(codec.decode::<World>(&[1]).is_none());
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn msgpack() {
+    use gw2lib::cache::MessagePack;
+
+    let bytes = MessagePack.encode(&world()).unwrap();
+    assert!(bytes.len() < Json.encode(&world()).unwrap().len());
+    let world: World = MessagePack.decode(&bytes).unwrap();
+    assert_eq!(world.name, self::world().name);
+    assert!(MessagePack.decode::<World>(b"{}").is_none());
+}
+
+#[cfg(all(feature = "msgpack", feature = "deflate"))]
+#[test]
+fn msgpack_deflate() {
+    use gw2lib::cache::{Deflate, MessagePack};
+
+    let codec = Compressed::new(MessagePack, Deflate::default());
+    let bytes = codec.encode(&world()).unwrap();
+    assert!(bytes.len() < MessagePack.encode(&world()).unwrap().len());
+
+    let world: World = codec.decode(&bytes).unwrap();
+    assert_eq!(world.name, self::world().name);
+    assert_eq!(world.population, PopulationLevel::High);
+    assert!(codec.decode::<World>(&[1, 2, 3]).is_none());
+}
@@ -0,0 +1,20 @@
+#![cfg(feature = "blocking")]
+
+use gw2lib::{
+    model::tradingpost::{CoinsToGems, ExchangeQuery, GemsToCoins},
+    Requester,
+};
+
+pub mod setup;
+
+#[test]
+fn coins() {
+    let client = setup::setup();
+    let _: CoinsToGems = client.query(&ExchangeQuery { quantity: 100_000 }).unwrap();
+}
+
+#[test]
+fn gems() {
+    let client = setup::setup();
+    let _: GemsToCoins = client.query(&ExchangeQuery { quantity: 100 }).unwrap();
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "blocking")]
+
+use gw2lib::{
+    model::authenticated::commerce::transactions::{
+        CurrentBuy, CurrentSell, HistoryBuy, HistorySell,
+    },
+    Requester,
+};
+
+pub mod setup;
+
+#[test]
+fn current() {
+    let client = setup::setup();
+    let _: Vec<CurrentBuy> = client.get_all_by_paging().unwrap();
+    let _: Vec<CurrentSell> = client.get_all_by_paging().unwrap();
+}
+
+#[test]
+fn history() {
+    let client = setup::setup();
+    let _ = client.page::<HistoryBuy>(0, 200).unwrap();
+    let _ = client.page::<HistorySell>(0, 200).unwrap();
+}
@@ -1,7 +1,7 @@
 #![cfg(feature = "blocking")]
 
 use gw2lib::{
-    model::maps::continents::{Continent, Floor},
+    model::maps::continents::{Continent, Floor, Map, Region},
     Requester,
 };
 
@@ -41,3 +41,15 @@ fn single_from_tuple() {
         .unwrap();
     assert_eq!(floor.id, 12);
 }
+
+#[test]
+fn regions_and_maps() {
+    let client = crate::setup::setup();
+    let regions: Vec<Region> = client.scoped_all((1, 1).into()).unwrap();
+    let region = regions.first().unwrap();
+    let maps: Vec<Map> = client
+        .scoped_ids::<Map, _, _>((1, 1, region.id).into())
+        .and_then(|ids| client.scoped_many((1, 1, region.id).into(), ids))
+        .unwrap();
+    assert_eq!(maps.len(), region.maps.len());
+}
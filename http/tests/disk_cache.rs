@@ -0,0 +1,223 @@
+#![cfg(all(feature = "blocking", feature = "disk", feature = "testing"))]
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use gw2lib::{
+    cache::{Cache, DiskCache},
+    model::{
+        authenticated::account::{
+            bank::Bank,
+            materials::{AccountMaterial, AccountMaterials},
+        },
+        misc::{
+            build::Build,
+            worlds::{PopulationLevel, World},
+        },
+        Language,
+    },
+    rate_limit::BucketRateLimiter,
+    testing::FakeApi,
+    Client, Requester,
+};
+use hyper::client::HttpConnector;
+
+fn cache_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("gw2lib_disk_{}_{name}", std::process::id()))
+}
+
+fn client(api: &FakeApi, dir: &Path) -> Client<DiskCache, BucketRateLimiter, HttpConnector, false> {
+    Client::default()
+        .host_http(api.url())
+        .cache(Arc::new(DiskCache::new(dir).unwrap()))
+}
+
+fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(fut)
+}
+
+#[test]
+fn survives_restart() {
+    let dir = cache_dir("survives_restart");
+    let api = FakeApi::start();
+    api.fixed(&Build { id: 1 });
+    api.bulk(&[World {
+        id: 1001,
+        name: "World".to_string(),
+        population: PopulationLevel::High,
+    }]);
+
+    {
+        let client = client(&api, &dir);
+        let _: Build = client.get().unwrap();
+        let _: World = client.single(1001).unwrap();
+    }
+
+    let client = client(&api, &dir);
+    let build: Build = client.get().unwrap();
+    assert_eq!(build.id, 1);
+    let world: World = client.single(1001).unwrap();
+    assert_eq!(world.name, "World");
+    assert_eq!(api.requests().len(), 2);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn expired() {
+    let dir = cache_dir("expired");
+    let api = FakeApi::start();
+    api.fixed(&Build { id: 1 });
+    api.max_age(0);
+    let client = client(&api, &dir);
+
+    let _: Build = client.get().unwrap();
+    // revalidated with the stored etag
+    let build: Build = client.get().unwrap();
+    assert_eq!(build.id, 1);
+    assert_eq!(api.requests().len(), 2);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn wipe() {
+    let dir = cache_dir("wipe");
+    let api = FakeApi::start();
+    api.api_key("key");
+    api.fixed(&Build { id: 1 });
+    api.fixed::<AccountMaterials>(&vec![AccountMaterial {
+        id: 19721,
+        category: 5,
+        count: 250,
+    }]);
+    let cache = Arc::new(DiskCache::new(&dir).unwrap());
+    let client = Client::empty()
+        .host_http(api.url())
+        .cache(cache.clone())
+        .api_key("key");
+
+    let _: Build = client.get().unwrap();
+    let _: AccountMaterials = client.get().unwrap();
+    block_on(cache.wipe_authenticated());
+    let _: Build = client.get().unwrap();
+    let _: AccountMaterials = client.get().unwrap();
+    assert_eq!(api.requests().len(), 3);
+
+    block_on(cache.wipe_static());
+    let _: Build = client.get().unwrap();
+    let _: AccountMaterials = client.get().unwrap();
+    assert_eq!(api.requests().len(), 4);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn cleanup() {
+    let dir = cache_dir("cleanup");
+    let api = FakeApi::start();
+    api.fixed(&Build { id: 1 });
+    let cache = Arc::new(DiskCache::new(&dir).unwrap());
+    let client = Client::empty().host_http(api.url()).cache(cache.clone());
+
+    let _: Build = client.get().unwrap();
+    std::fs::write(dir.join("static").join("broken.json"), "{").unwrap();
+    block_on(cache.cleanup());
+
+    let files: Vec<_> = std::fs::read_dir(dir.join("static")).unwrap().collect();
+    assert_eq!(files.len(), 1);
+    let _: Build = client.get().unwrap();
+    assert_eq!(api.requests().len(), 1);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn invalidate() {
+    let dir = cache_dir("invalidate");
+    let api = FakeApi::start();
+    api.api_key("key");
+    api.fixed(&Build { id: 1 });
+    api.fixed::<AccountMaterials>(&vec![]);
+    api.bulk(&[World {
+        id: 1001,
+        name: "World".to_string(),
+        population: PopulationLevel::High,
+    }]);
+    let cache = Arc::new(DiskCache::new(&dir).unwrap());
+    let client = Client::empty()
+        .host_http(api.url())
+        .cache(cache.clone())
+        .api_key("key");
+    let requests = |count| {
+        let _: Build = client.get().unwrap();
+        let _: World = client.single(1001).unwrap();
+        let _: AccountMaterials = client.get().unwrap();
+        assert_eq!(api.requests().len(), count);
+    };
+
+    requests(3);
+    block_on(cache.remove::<World, u16, World, String>(&1001, Language::En, &None));
+    requests(4);
+    block_on(cache.wipe_endpoint::<Build>());
+    requests(5);
+    block_on(cache.wipe_identifier(&"other".to_string()));
+    requests(5);
+    block_on(cache.wipe_identifier(&"key".to_string()));
+    requests(6);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn wipe_identifier_exact() {
+    let dir = cache_dir("wipe_identifier_exact");
+    let api = FakeApi::start();
+    api.api_key("key");
+    api.api_key("key_2");
+    api.fixed::<AccountMaterials>(&vec![]);
+    let cache = Arc::new(DiskCache::new(&dir).unwrap());
+    let client = Client::empty().host_http(api.url()).cache(cache.clone());
+    let requests = |key: &str, count| {
+        let _: AccountMaterials = client.clone().api_key(key).get().unwrap();
+        assert_eq!(api.requests().len(), count);
+    };
+
+    requests("key", 1);
+    requests("key_2", 2);
+    block_on(cache.wipe_identifier(&"key".to_string()));
+    requests("key_2", 2);
+    requests("key", 3);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn wipe_endpoint_escaped_identifier() {
+    let dir = cache_dir("wipe_endpoint_escaped_identifier");
+    let api = FakeApi::start();
+    api.api_key("key_2");
+    api.fixed::<AccountMaterials>(&vec![]);
+    api.fixed::<Bank>(&vec![]);
+    let cache = Arc::new(DiskCache::new(&dir).unwrap());
+    let client = Client::empty()
+        .host_http(api.url())
+        .cache(cache.clone())
+        .api_key("key_2");
+
+    let _: AccountMaterials = client.get().unwrap();
+    let _: Bank = client.get().unwrap();
+    block_on(cache.wipe_endpoint::<AccountMaterials>());
+    let _: Bank = client.get().unwrap();
+    assert_eq!(api.requests().len(), 2);
+    let _: AccountMaterials = client.get().unwrap();
+    assert_eq!(api.requests().len(), 3);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,1457 @@
+#![cfg(all(feature = "blocking", feature = "testing"))]
+
+use std::time::Duration;
+
+use gw2lib::{
+    cache::{CachePolicy, InMemoryCache, NoopCache},
+    model::{
+        authenticated::account::materials::{AccountMaterial, AccountMaterials},
+        misc::{
+            build::Build,
+            worlds::{PopulationLevel, World, WorldId},
+        },
+        tradingpost::Listings,
+        Language,
+    },
+    rate_limit::BucketRateLimiter,
+    retry::RetryPolicy,
+    testing::{FakeApi, FakeResponse},
+    ApiError, Client, EndpointError, Priority, Requester,
+};
+use hyper::{client::HttpConnector, StatusCode};
+
+fn client(api: &FakeApi) -> Client<InMemoryCache, BucketRateLimiter, HttpConnector, false> {
+    Client::default()
+        .host_http(api.url())
+        .retry(RetryPolicy::new(2).base_delay(Duration::from_millis(1)))
+}
+
+fn worlds(name: &str) -> Vec<World> {
+    (1001..=1003)
+        .map(|id| World {
+            id,
+            name: format!("{name} {id}"),
+            population: PopulationLevel::High,
+        })
+        .collect()
+}
+
+#[test]
+fn get() {
+    let api = FakeApi::start();
+    api.fixed(&Build { id: 115267 });
+    let client = client(&api);
+
+    let build: Build = client.get().unwrap();
+    assert_eq!(build.id, 115267);
+}
+
+mod cache {
+    use super::*;
+
+    #[test]
+    fn hit() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        let client = client(&api);
+
+        let _: Build = client.get().unwrap();
+        let _: Build = client.get().unwrap();
+        assert_eq!(api.requests().len(), 1);
+    }
+
+    #[test]
+    fn forced() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        let client = client(&api);
+
+        let _: Build = client.get().unwrap();
+        api.fixed(&Build { id: 2 });
+        let build: Build = client.forced().get().unwrap();
+        assert_eq!(build.id, 2);
+        assert_eq!(api.requests().len(), 2);
+    }
+
+    #[test]
+    fn expired() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        api.max_age(0);
+        let client = client(&api);
+
+        let _: Build = client.get().unwrap();
+        let _: Build = client.get().unwrap();
+        assert_eq!(api.requests().len(), 2);
+    }
+
+    #[test]
+    fn revalidate() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        api.max_age(0);
+        let client = client(&api);
+
+        let _: Build = client.get().unwrap();
+        // not modified
+        let build: Build = client.get().unwrap();
+        assert_eq!(build.id, 1);
+
+        api.fixed(&Build { id: 2 });
+        let build: Build = client.get().unwrap();
+        assert_eq!(build.id, 2);
+        assert_eq!(api.requests().len(), 3);
+    }
+
+    #[test]
+    fn revalidate_single() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        api.max_age(0);
+        let client = client(&api);
+
+        let _: World = client.single(1001).unwrap();
+        let world: World = client.single(1001).unwrap();
+        assert_eq!(world.name, "World 1001");
+        assert_eq!(api.requests().len(), 2);
+    }
+
+    #[test]
+    fn many() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let _: World = client.single(1001).unwrap();
+        let worlds: Vec<World> = client.many(vec![1001, 1002]).unwrap();
+        assert_eq!(worlds.len(), 2);
+
+        let requests = api.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains("ids=1002&"));
+    }
+}
+
+mod stale {
+    use super::*;
+
+    fn wait_for_requests(api: &FakeApi, count: usize) {
+        for _ in 0..100 {
+            if api.requests().len() >= count {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("expected {count} requests, got {:?}", api.requests());
+    }
+
+    #[test]
+    fn while_revalidate() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        api.max_age(0);
+        let policy = CachePolicy::default().stale_while_revalidate(chrono::Duration::minutes(1));
+        let client = client(&api).cache_policy(policy);
+
+        let _: World = client.single(1001).unwrap();
+        api.bulk(&worlds("Welt"));
+        let world: World = client.single(1001).unwrap();
+        assert_eq!(world.name, "World 1001");
+
+        wait_for_requests(&api, 2);
+        let world: World = client.single(1001).unwrap();
+        assert_eq!(world.name, "Welt 1001");
+    }
+
+    #[test]
+    fn while_revalidate_many() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        api.max_age(0);
+        let policy = CachePolicy::default().stale_while_revalidate(chrono::Duration::minutes(1));
+        let client = client(&api).cache_policy(policy);
+
+        let _: Vec<World> = client.many(vec![1001, 1002]).unwrap();
+        api.bulk(&worlds("Welt"));
+        let worlds: Vec<World> = client.many(vec![1001, 1002]).unwrap();
+        assert!(worlds.iter().all(|x| x.name.starts_with("World")));
+
+        wait_for_requests(&api, 2);
+        let worlds: Vec<World> = client.many(vec![1001, 1002]).unwrap();
+        assert!(worlds.iter().all(|x| x.name.starts_with("Welt")));
+    }
+
+    #[test]
+    fn if_error() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        api.max_age(0);
+        let policy = CachePolicy::default().stale_if_error(chrono::Duration::minutes(1));
+        let client = client(&api)
+            .retry(RetryPolicy::disabled())
+            .cache_policy(policy);
+
+        let _: Build = client.get().unwrap();
+        api.respond_with(
+            "v2/build",
+            FakeResponse::new(StatusCode::SERVICE_UNAVAILABLE),
+        );
+        let build: Build = client.get().unwrap();
+        assert_eq!(build.id, 1);
+        assert_eq!(api.requests().len(), 2);
+    }
+
+    #[test]
+    fn if_error_many() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        api.max_age(0);
+        let policy = CachePolicy::default().stale_if_error(chrono::Duration::minutes(1));
+        let client = client(&api)
+            .retry(RetryPolicy::disabled())
+            .cache_policy(policy);
+
+        let _: World = client.single(1001).unwrap();
+        api.respond_with(
+            "v2/worlds",
+            FakeResponse::new(StatusCode::SERVICE_UNAVAILABLE),
+        );
+        let res: Result<Vec<World>, _> = client.many(vec![1001, 1002]);
+        assert!(res.is_err());
+
+        api.respond_with(
+            "v2/worlds",
+            FakeResponse::new(StatusCode::SERVICE_UNAVAILABLE),
+        );
+        let worlds: Vec<World> = client.many(vec![1001]).unwrap();
+        assert_eq!(worlds[0].name, "World 1001");
+    }
+
+    #[test]
+    fn without_policy() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        api.max_age(0);
+        let client = client(&api).retry(RetryPolicy::disabled());
+
+        let _: Build = client.get().unwrap();
+        api.respond_with(
+            "v2/build",
+            FakeResponse::new(StatusCode::SERVICE_UNAVAILABLE),
+        );
+        let res: Result<Build, _> = client.get();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn client_error() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        api.max_age(0);
+        let policy = CachePolicy::default().stale_if_error(chrono::Duration::minutes(1));
+        let client = client(&api).cache_policy(policy);
+
+        let _: Build = client.get().unwrap();
+        api.respond_with("v2/build", FakeResponse::new(StatusCode::BAD_REQUEST));
+        let res: Result<Build, _> = client.get();
+        assert!(res.is_err());
+    }
+}
+
+mod bulk {
+    use super::*;
+
+    #[test]
+    fn ids() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let ids: Vec<WorldId> = client.ids::<World, WorldId>().unwrap();
+        assert_eq!(ids, vec![1001, 1002, 1003]);
+    }
+
+    #[test]
+    fn all() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let worlds: Vec<World> = client.all().unwrap();
+        assert_eq!(worlds.len(), 3);
+        assert!(api.requests()[0].contains("ids=all"));
+    }
+
+    #[test]
+    fn partial() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let worlds: Vec<World> = client.many(vec![1001, 9999]).unwrap();
+        assert_eq!(worlds.len(), 1);
+    }
+
+    #[test]
+    fn unexpected_entries() {
+        let api = FakeApi::start();
+        let worlds = worlds("World");
+        api.bulk(&worlds);
+        // the first world twice and one that was not asked for
+        let answer = vec![worlds[0].clone(), worlds[0].clone(), worlds[2].clone()];
+        api.respond_with("v2/worlds", FakeResponse::json(StatusCode::OK, answer));
+        let client = client(&api);
+
+        let worlds: Vec<World> = client.many(vec![1001, 1002]).unwrap();
+        let ids: Vec<_> = worlds.iter().map(|x| x.id).collect();
+        assert_eq!(ids, [1001]);
+    }
+
+    #[test]
+    fn paging() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let page = client.page::<World>(1, 2).unwrap();
+        assert_eq!(page.result_total, 3);
+        assert_eq!(page.items.len(), 1);
+    }
+
+    #[test]
+    fn localized() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        api.bulk_in(Language::De, &worlds("Welt"));
+        let client = client(&api).language(Language::De);
+
+        let world: World = client.single(1001).unwrap();
+        assert_eq!(world.name, "Welt 1001");
+    }
+}
+
+mod paging {
+    use gw2lib::model::authenticated::commerce::transactions::{HistoryBuy, Transaction};
+
+    use super::*;
+
+    fn history(count: u64) -> Vec<HistoryBuy> {
+        (0..count)
+            .map(|id| {
+                HistoryBuy(Transaction {
+                    id,
+                    item_id: 19721,
+                    price: 100,
+                    quantity: 1,
+                    created: "2024-01-01T00:00:00+00:00".to_string(),
+                    purchased: Some("2024-01-02T00:00:00+00:00".to_string()),
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn metadata() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let page = client.page::<World>(0, 2).unwrap();
+        assert_eq!(page.page, 0);
+        assert_eq!(page.page_total, 2);
+        assert_eq!(page.page_size, 2);
+        assert_eq!(page.result_count, 2);
+        assert_eq!(page.result_total, 3);
+        assert!(!page.is_last());
+        assert_eq!(page.links["next"], "/v2/worlds?page=1&page_size=2");
+        assert_eq!(page.links["last"], "/v2/worlds?page=1&page_size=2");
+    }
+
+    #[test]
+    fn pages() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let pages: Vec<_> = client.pages::<World>(1).collect::<Result<_, _>>().unwrap();
+        let ids: Vec<_> = pages.iter().map(|x| x.items[0].id).collect();
+        assert_eq!(ids, vec![1001, 1002, 1003]);
+        assert!(pages[2].is_last());
+        assert_eq!(api.requests().len(), 3);
+    }
+
+    #[test]
+    fn pages_bounded() {
+        let api = FakeApi::start();
+        let world = worlds("World").remove(0);
+        for _ in 0..9 {
+            let page = FakeResponse::json(StatusCode::OK, [&world])
+                .header("x-page-total", "9")
+                .delay(Duration::from_millis(100));
+            api.respond_with("v2/worlds", page);
+        }
+        let client = client(&api);
+
+        let start = std::time::Instant::now();
+        let pages: Vec<_> = client.pages::<World>(1).collect::<Result<_, _>>().unwrap();
+        assert_eq!(pages.len(), 9);
+        // the first page, then two rounds of four
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn out_of_range() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let err = client.page::<World>(5, 2).unwrap_err();
+        assert!(matches!(err.api_error(), Some(ApiError::PageOutOfRange)));
+    }
+
+    #[test]
+    fn non_bulk() {
+        let api = FakeApi::start();
+        api.api_key("key");
+        api.paged(&history(450));
+        let client = client(&api).api_key("key");
+
+        let all: Vec<HistoryBuy> = client.get_all_by_paging().unwrap();
+        assert_eq!(all.len(), 450);
+        assert_eq!(all[449].id, 449);
+        assert_eq!(api.requests().len(), 3);
+    }
+}
+
+mod query {
+    use gw2lib::model::{
+        guild::search::{GuildSearch, GuildSearchQuery},
+        tradingpost::{CoinsToGems, ExchangeQuery},
+    };
+
+    use super::*;
+
+    fn exchange(coins: u64) -> (ExchangeQuery, CoinsToGems) {
+        let query = ExchangeQuery { quantity: coins };
+        let gems = CoinsToGems {
+            coins_per_gem: 2500,
+            quantity: coins / 2500,
+        };
+        (query, gems)
+    }
+
+    #[test]
+    fn params() {
+        let api = FakeApi::start();
+        let (query, gems) = exchange(100_000);
+        api.query(&query, &gems);
+        let client = client(&api);
+
+        let res: CoinsToGems = client.query(&query).unwrap();
+        assert_eq!(res.quantity, 40);
+        assert!(api.requests()[0].contains("quantity=100000"));
+    }
+
+    #[test]
+    fn cached_per_params() {
+        let api = FakeApi::start();
+        let (small, small_gems) = exchange(10_000);
+        let (large, large_gems) = exchange(100_000);
+        api.query(&small, &small_gems);
+        api.query(&large, &large_gems);
+        let client = client(&api);
+
+        let res: CoinsToGems = client.query(&small).unwrap();
+        assert_eq!(res.quantity, 4);
+        let res: CoinsToGems = client.query(&large).unwrap();
+        assert_eq!(res.quantity, 40);
+        let res: CoinsToGems = client.query(&small).unwrap();
+        assert_eq!(res.quantity, 4);
+        assert_eq!(api.requests().len(), 2);
+    }
+
+    #[test]
+    fn encoded() {
+        let api = FakeApi::start();
+        let query = GuildSearchQuery {
+            name: "Guild Wars & Co".to_string(),
+        };
+        api.query(&query, &GuildSearch(vec!["guild-id".to_string()]));
+        let client = client(&api);
+
+        let res: GuildSearch = client.query(&query).unwrap();
+        assert_eq!(res.0, ["guild-id"]);
+    }
+}
+
+mod scoped {
+    use gw2lib::model::{
+        authenticated::characters::{BuildTab, Core},
+        guild::members::{GuildMember, GuildMembers},
+    };
+
+    use super::*;
+
+    fn build_tab(tab: usize) -> BuildTab {
+        let skills = serde_json::json!({
+            "heal": null,
+            "utilities": [null, null, null],
+            "elite": null,
+            "legends": null,
+        });
+        serde_json::from_value(serde_json::json!({
+            "tab": tab,
+            "is_active": tab == 1,
+            "build": {
+                "name": format!("Tab {tab}"),
+                "profession": null,
+                "specializations": [
+                    { "id": null, "traits": null },
+                    { "id": null, "traits": null },
+                    { "id": null, "traits": null },
+                ],
+                "skills": skills,
+                "aquatic_skills": skills,
+                "pets": null,
+                "legends": null,
+                "aquatic_legends": null,
+            },
+        }))
+        .unwrap()
+    }
+
+    fn character(api: &FakeApi) -> Client<InMemoryCache, BucketRateLimiter, HttpConnector, true> {
+        api.api_key("key");
+        let tabs: Vec<_> = (1..=3).map(build_tab).collect();
+        api.scoped_bulk(&"My Character".to_string(), &tabs);
+        client(api).api_key("key")
+    }
+
+    #[test]
+    fn cached_per_parent() {
+        let api = FakeApi::start();
+        api.api_key("key");
+        let member = |name: &str| GuildMember {
+            name: name.to_string(),
+            rank: "Leader".to_string(),
+            joined: None,
+            wvw_member: false,
+        };
+        api.scoped(
+            &"guild-a".to_string(),
+            &GuildMembers(vec![member("a.1234")]),
+        );
+        api.scoped(
+            &"guild-b".to_string(),
+            &GuildMembers(vec![member("b.1234"), member("c.1234")]),
+        );
+        let client = client(&api).api_key("key");
+
+        let a: GuildMembers = client.scoped("guild-a".to_string()).unwrap();
+        assert_eq!(a[0].name, "a.1234");
+        let b: GuildMembers = client.scoped("guild-b".to_string()).unwrap();
+        assert_eq!(b.len(), 2);
+        let _: GuildMembers = client.scoped("guild-a".to_string()).unwrap();
+        let requests = api.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("/v2/guild/guild-a/members"));
+    }
+
+    #[test]
+    fn character_core() {
+        let api = FakeApi::start();
+        api.api_key("key");
+        api.raw(
+            "v2/characters/My%20Character/core",
+            serde_json::json!({
+                "name": "My Character",
+                "race": "Human",
+                "gender": "Female",
+                "profession": "Guardian",
+                "level": 80,
+                "guild": null,
+                "age": 3600,
+                "created": "2020-01-01T00:00:00Z",
+                "last_modified": "2020-01-02T00:00:00Z",
+                "deaths": 0,
+                "title": null,
+            }),
+        );
+        let client = client(&api).api_key("key");
+
+        let core: Core = client.scoped("My Character".to_string()).unwrap();
+        assert_eq!(core.level, 80);
+    }
+
+    #[test]
+    fn ids_and_single() {
+        let api = FakeApi::start();
+        let client = character(&api);
+
+        let ids = client
+            .scoped_ids::<BuildTab, _, _>("My Character".to_string())
+            .unwrap();
+        assert_eq!(ids, [1, 2, 3]);
+        let tab: BuildTab = client.scoped_single("My Character".to_string(), 2).unwrap();
+        assert_eq!(tab.build.name.as_deref(), Some("Tab 2"));
+        assert!(api.requests()[1].starts_with("/v2/characters/My%20Character/buildtabs/2"));
+    }
+
+    #[test]
+    fn many_uses_cache() {
+        let api = FakeApi::start();
+        let client = character(&api);
+
+        let _: BuildTab = client.scoped_single("My Character".to_string(), 1).unwrap();
+        let tabs: Vec<BuildTab> = client
+            .scoped_many("My Character".to_string(), vec![1, 2, 9])
+            .unwrap();
+        assert_eq!(tabs.len(), 2);
+        let requests = api.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains("tabs=2,9"));
+
+        let tabs: Vec<BuildTab> = client
+            .scoped_many("My Character".to_string(), vec![1, 2])
+            .unwrap();
+        assert_eq!(tabs.len(), 2);
+        assert_eq!(api.requests().len(), 2);
+    }
+
+    #[test]
+    fn many_keeps_order() {
+        let api = FakeApi::start();
+        let client = character(&api);
+
+        let _: BuildTab = client.scoped_single("My Character".to_string(), 2).unwrap();
+        let tabs: Vec<BuildTab> = client
+            .scoped_many("My Character".to_string(), vec![3, 2, 1])
+            .unwrap();
+        let tabs: Vec<_> = tabs.iter().map(|t| t.tab).collect();
+        assert_eq!(tabs, [3, 2, 1]);
+    }
+
+    #[test]
+    fn many_caches_unknown_ids() {
+        let api = FakeApi::start();
+        let client = character(&api);
+
+        let tabs: Vec<BuildTab> = client
+            .scoped_many("My Character".to_string(), vec![1, 9])
+            .unwrap();
+        assert_eq!(tabs.len(), 1);
+        let tabs: Vec<BuildTab> = client
+            .scoped_many("My Character".to_string(), vec![9])
+            .unwrap();
+        assert!(tabs.is_empty());
+        assert_eq!(api.requests().len(), 1);
+    }
+
+    #[test]
+    fn all() {
+        let api = FakeApi::start();
+        let client = character(&api);
+
+        let tabs: Vec<BuildTab> = client.scoped_all("My Character".to_string()).unwrap();
+        assert_eq!(tabs.len(), 3);
+        let tab: BuildTab = client.scoped_single("My Character".to_string(), 3).unwrap();
+        assert_eq!(tab.tab, 3);
+        let requests = api.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("tabs=all"));
+    }
+}
+
+mod raw {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Clone, Serialize, Deserialize)]
+    struct Vault {
+        title: String,
+    }
+
+    fn vault(api: &FakeApi) {
+        api.raw(
+            "v2/wizardsvault",
+            serde_json::json!({ "title": "Season", "start": "2026-01-01T00:00:00Z" }),
+        );
+    }
+
+    #[test]
+    fn value() {
+        let api = FakeApi::start();
+        vault(&api);
+        let client = client(&api);
+
+        let res = client.raw("v2/wizardsvault", &()).unwrap();
+        assert_eq!(res["title"], "Season");
+        let request = &api.requests()[0];
+        assert!(request.starts_with("/v2/wizardsvault?v=latest"));
+        assert!(!request.contains("lang="));
+    }
+
+    #[test]
+    fn language_opt_in() {
+        let api = FakeApi::start();
+        vault(&api);
+        let client = client(&api);
+
+        let _ = client.raw("v2/wizardsvault", &[("lang", "de")]).unwrap();
+        let request = &api.requests()[0];
+        assert!(request.contains("lang=de"));
+        assert!(!request.contains("lang=en"));
+    }
+
+    #[test]
+    fn typed_with_query() {
+        let api = FakeApi::start();
+        vault(&api);
+        let client = client(&api);
+
+        let res: Vault = client
+            .raw_as("/v2/wizardsvault", &[("ids", "1,2")])
+            .unwrap();
+        assert_eq!(res.title, "Season");
+        assert!(api.requests()[0].contains("ids=1%2C2"));
+    }
+
+    #[test]
+    fn cached_per_query() {
+        let api = FakeApi::start();
+        vault(&api);
+        let client = client(&api);
+
+        let _ = client.raw("v2/wizardsvault", &()).unwrap();
+        let _ = client.raw("v2/wizardsvault", &()).unwrap();
+        assert_eq!(api.requests().len(), 1);
+        let _ = client.raw("v2/wizardsvault", &[("id", "1")]).unwrap();
+        assert_eq!(api.requests().len(), 2);
+        let _ = client.forced().raw("v2/wizardsvault", &()).unwrap();
+        assert_eq!(api.requests().len(), 3);
+    }
+
+    #[test]
+    fn errors() {
+        let api = FakeApi::start();
+        let client = client(&api);
+
+        let err = client.raw("v2/unmapped", &()).unwrap_err();
+        assert!(matches!(err.api_error(), Some(ApiError::NotFound)));
+        assert!(err.context().unwrap().url.starts_with("/v2/unmapped?"));
+    }
+}
+
+mod missing {
+    use super::*;
+
+    #[test]
+    fn many_detailed() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let res = client
+            .many_detailed::<World, WorldId>(vec![1001, 9999])
+            .unwrap();
+        assert_eq!(res.found.len(), 1);
+        assert_eq!(res.missing, vec![9999]);
+
+        let res = client
+            .many_detailed::<World, WorldId>(vec![1001, 9999])
+            .unwrap();
+        assert_eq!(res.found.len(), 1);
+        assert_eq!(res.missing, vec![9999]);
+        assert_eq!(api.requests().len(), 1);
+    }
+
+    #[test]
+    fn all_invalid() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let res = client
+            .many_detailed::<World, WorldId>(vec![9998, 9999])
+            .unwrap();
+        assert!(res.found.is_empty());
+        assert_eq!(res.missing.len(), 2);
+
+        let worlds: Vec<World> = client.many(vec![9998, 9999]).unwrap();
+        assert!(worlds.is_empty());
+        assert_eq!(api.requests().len(), 1);
+    }
+
+    #[test]
+    fn single() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        for _ in 0..2 {
+            let res: Result<World, _> = client.single(9999);
+            assert!(matches!(
+                res,
+                Err(EndpointError::ApiError(ApiError::NotFound, _))
+            ));
+        }
+        assert_eq!(api.requests().len(), 1);
+    }
+
+    #[test]
+    fn disabled() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api).cache_not_found(chrono::Duration::zero());
+
+        for _ in 0..2 {
+            let worlds: Vec<World> = client.many(vec![9999]).unwrap();
+            assert!(worlds.is_empty());
+        }
+        assert_eq!(api.requests().len(), 2);
+    }
+}
+
+mod stream {
+    use super::*;
+
+    fn listings(count: u32) -> Vec<Listings> {
+        (1..=count)
+            .map(|id| Listings {
+                id,
+                buys: vec![],
+                sells: vec![],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn many() {
+        let api = FakeApi::start();
+        api.bulk(&listings(450));
+        let client = client(&api);
+
+        let _: Listings = client.single(1).unwrap();
+        let mut stream = client.stream_many::<Listings, _>((1..=450).collect());
+        // cached entries come first
+        assert_eq!(stream.next().unwrap().unwrap().id, 1);
+        assert_eq!(stream.count(), 449);
+        // one single request and three chunks
+        assert_eq!(api.requests().len(), 4);
+
+        let cached: Vec<Listings> = client.many((1..=450).collect::<Vec<_>>()).unwrap();
+        assert_eq!(cached.len(), 450);
+        assert_eq!(api.requests().len(), 4);
+    }
+
+    #[test]
+    fn all_by_ids() {
+        let api = FakeApi::start();
+        api.bulk(&listings(250));
+        let client = client(&api);
+
+        let all: Result<Vec<Listings>, _> = client.stream_all().collect();
+        assert_eq!(all.unwrap().len(), 250);
+        // ids and two chunks
+        assert_eq!(api.requests().len(), 3);
+    }
+
+    #[test]
+    fn all_by_ids_all() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let all: Result<Vec<World>, _> = client.stream_all().collect();
+        assert_eq!(all.unwrap().len(), 3);
+        assert_eq!(api.requests().len(), 1);
+    }
+
+    #[test]
+    fn failed_chunk() {
+        let api = FakeApi::start();
+        api.bulk(&listings(300));
+        for _ in 0..2 {
+            api.respond_with(
+                "v2/commerce/listings",
+                FakeResponse::new(StatusCode::SERVICE_UNAVAILABLE),
+            );
+        }
+        let client = client(&api).retry(RetryPolicy::disabled());
+
+        let results: Vec<_> = client
+            .stream_many::<Listings, _>((1..=300).collect())
+            .collect();
+        assert_eq!(results.iter().filter(|x| x.is_err()).count(), 2);
+
+        let retried: Vec<Listings> = client.many((1..=300).collect::<Vec<_>>()).unwrap();
+        assert_eq!(retried.len(), 300);
+    }
+}
+
+mod build {
+    use super::*;
+
+    fn world_requests(api: &FakeApi) -> usize {
+        api.requests()
+            .iter()
+            .filter(|x| x.contains("v2/worlds"))
+            .count()
+    }
+
+    #[test]
+    fn wipes_on_new_build() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        api.bulk(&worlds("World"));
+        let client = client(&api).wipe_on_new_build(Duration::from_millis(10));
+
+        let _: World = client.single(1001).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        let _: World = client.single(1001).unwrap();
+        assert_eq!(world_requests(&api), 1);
+
+        api.fixed(&Build { id: 2 });
+        for _ in 0..100 {
+            let _: World = client.single(1001).unwrap();
+            if world_requests(&api) == 2 {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("static cache was not wiped");
+    }
+}
+
+mod auth {
+    use super::*;
+
+    #[test]
+    fn valid_key() {
+        let api = FakeApi::start();
+        api.api_key("key");
+        api.fixed::<AccountMaterials>(&vec![AccountMaterial {
+            id: 19721,
+            category: 5,
+            count: 250,
+        }]);
+        let client = client(&api).api_key("key");
+
+        let materials: AccountMaterials = client.get().unwrap();
+        assert_eq!(materials.len(), 1);
+    }
+
+    #[test]
+    fn invalid_key() {
+        let api = FakeApi::start();
+        api.api_key("key");
+        api.fixed::<AccountMaterials>(&vec![]);
+        let client = client(&api).api_key("wrong-key");
+
+        let res: Result<AccountMaterials, _> = client.get();
+        assert!(matches!(
+            res,
+            Err(EndpointError::ApiError(ApiError::Unauthorized, _))
+        ));
+    }
+
+    #[test]
+    fn key_not_in_uri() {
+        let api = FakeApi::start();
+        api.api_key("secret-key");
+        api.fixed::<AccountMaterials>(&vec![]);
+        let client = client(&api).api_key("secret-key");
+
+        let _: AccountMaterials = client.get().unwrap();
+        let requests = api.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(!requests[0].contains("secret-key"));
+        assert!(!requests[0].contains("access_token"));
+    }
+
+    #[test]
+    fn redacts_errors() {
+        let api = FakeApi::start();
+        api.respond_with(
+            "v2/account/materials",
+            FakeResponse::error(StatusCode::BAD_REQUEST, "unknown token secret-key"),
+        );
+        let client = client(&api).api_key("secret-key");
+
+        let err = client.get::<AccountMaterials>().unwrap_err().to_string();
+        assert!(!err.contains("secret-key"), "{err}");
+        assert!(err.contains("unknown token"), "{err}");
+    }
+
+    #[test]
+    fn redacts_debug() {
+        let client = Client::empty().api_key("secret-key");
+        let debug = format!("{client:?}");
+        assert!(!debug.contains("secret-key"), "{debug}");
+
+        let client = client.identifier("account-id");
+        assert!(format!("{client:?}").contains("account-id"));
+    }
+}
+
+mod errors {
+    use super::*;
+
+    #[test]
+    fn context() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        api.respond_with(
+            "v2/worlds",
+            FakeResponse::error(StatusCode::INTERNAL_SERVER_ERROR, "ErrInternal")
+                .header("x-request-id", "abc"),
+        );
+        let client = client(&api).retry(RetryPolicy::disabled());
+
+        let err = client.many::<World, WorldId>(vec![1001, 1002]).unwrap_err();
+        assert!(matches!(
+            err.api_error(),
+            Some(ApiError::Other(StatusCode::INTERNAL_SERVER_ERROR, _))
+        ));
+        let context = err.context().unwrap();
+        assert!(context.url.starts_with("/v2/worlds?"));
+        assert_eq!(context.ids, ["1001", "1002"]);
+        assert_eq!(context.status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(context.headers["x-request-id"], "abc");
+        assert!(err.to_string().contains("/v2/worlds?"));
+    }
+
+    #[test]
+    fn single_context() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        let client = client(&api);
+
+        let err = client.single::<World, WorldId>(9999).unwrap_err();
+        let context = err.context().unwrap();
+        assert_eq!(context.ids, ["9999"]);
+        assert_eq!(context.status, StatusCode::NOT_FOUND);
+
+        // known to be missing, answered from the cache
+        let err = client.single::<World, WorldId>(9999).unwrap_err();
+        assert_eq!(err.context().unwrap().ids, ["9999"]);
+        assert_eq!(api.requests().len(), 1);
+    }
+
+    #[test]
+    fn known_texts() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        api.respond_with(
+            "v2/build",
+            FakeResponse::error(StatusCode::SERVICE_UNAVAILABLE, "API not active"),
+        );
+        api.respond_with(
+            "v2/build",
+            FakeResponse::new(StatusCode::SERVICE_UNAVAILABLE),
+        );
+        let client = client(&api).retry(RetryPolicy::disabled());
+
+        let err = client.get::<Build>().unwrap_err();
+        assert!(matches!(err.api_error(), Some(ApiError::NotActive)));
+        let err = client.get::<Build>().unwrap_err();
+        assert!(matches!(err.api_error(), Some(ApiError::Unavailable)));
+    }
+
+    #[test]
+    fn invalid_json() {
+        let api = FakeApi::start();
+        api.respond_with(
+            "v2/build",
+            FakeResponse::json(StatusCode::OK, serde_json::json!({ "id": "latest" })),
+        );
+        let client = client(&api);
+
+        let err = client.get::<Build>().unwrap_err();
+        let EndpointError::InvalidJsonResponse {
+            path,
+            excerpt,
+            context,
+            ..
+        } = &err
+        else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(path, "id");
+        assert!(excerpt.contains("latest"));
+        assert!(context.url.starts_with("/v2/build?"));
+    }
+}
+
+mod retry {
+    use std::time::Instant;
+
+    use super::*;
+
+    #[test]
+    fn recovers() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        api.respond_with(
+            "v2/build",
+            FakeResponse::error(StatusCode::SERVICE_UNAVAILABLE, "API not active"),
+        );
+        let client = client(&api);
+
+        let _: Build = client.get().unwrap();
+        assert_eq!(api.requests().len(), 2);
+    }
+
+    #[test]
+    fn exhausted() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        for _ in 0..2 {
+            api.respond_with("v2/build", FakeResponse::new(StatusCode::BAD_GATEWAY));
+        }
+        let client = client(&api);
+
+        let res: Result<Build, _> = client.get();
+        assert!(matches!(
+            res,
+            Err(EndpointError::ApiError(
+                ApiError::Other(StatusCode::BAD_GATEWAY, _),
+                _
+            ))
+        ));
+        assert_eq!(api.requests().len(), 2);
+    }
+
+    #[test]
+    fn disabled() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        api.respond_with(
+            "v2/build",
+            FakeResponse::error(StatusCode::TOO_MANY_REQUESTS, "too many requests"),
+        );
+        let client = client(&api).retry(RetryPolicy::disabled());
+
+        let res: Result<Build, _> = client.get();
+        assert!(matches!(
+            res,
+            Err(EndpointError::ApiError(ApiError::RateLimited, _))
+        ));
+    }
+
+    #[test]
+    fn bulk_chunk() {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        api.respond_with("v2/worlds", FakeResponse::new(StatusCode::GATEWAY_TIMEOUT));
+        let client = client(&api);
+
+        let worlds: Vec<World> = client.many(vec![1001, 1002]).unwrap();
+        assert_eq!(worlds.len(), 2);
+    }
+
+    /// sends a build request failing `failures` times and returns how long
+    /// it took
+    fn timed(failures: usize, response: FakeResponse, policy: RetryPolicy) -> Duration {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        for _ in 0..failures {
+            api.respond_with("v2/build", response.clone());
+        }
+        let client = client(&api).retry(policy);
+
+        let start = Instant::now();
+        let _: Build = client.get().unwrap();
+        assert_eq!(api.requests().len(), failures + 1);
+        start.elapsed()
+    }
+
+    #[test]
+    fn max_attempts() {
+        let api = FakeApi::start();
+        api.fixed(&Build { id: 1 });
+        for _ in 0..3 {
+            api.respond_with("v2/build", FakeResponse::new(StatusCode::BAD_GATEWAY));
+        }
+        let client = client(&api).retry(RetryPolicy::new(3).base_delay(Duration::from_millis(1)));
+
+        let res: Result<Build, _> = client.get();
+        assert!(res.is_err());
+        assert_eq!(api.requests().len(), 3);
+        let _: Build = client.get().unwrap();
+        assert_eq!(api.requests().len(), 4);
+    }
+
+    #[test]
+    fn backoff() {
+        // waits 100-200ms, then 200-400ms
+        let policy = RetryPolicy::new(3).base_delay(Duration::from_millis(200));
+        let elapsed = timed(2, FakeResponse::new(StatusCode::BAD_GATEWAY), policy);
+        assert!(elapsed >= Duration::from_millis(300), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(1000), "{elapsed:?}");
+    }
+
+    #[test]
+    fn max_delay() {
+        // the second delay would be 200-400ms without the bound
+        let policy = RetryPolicy::new(3)
+            .base_delay(Duration::from_millis(200))
+            .max_delay(Duration::from_millis(100));
+        let elapsed = timed(2, FakeResponse::new(StatusCode::BAD_GATEWAY), policy);
+        assert!(elapsed >= Duration::from_millis(100), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(300), "{elapsed:?}");
+    }
+
+    #[test]
+    fn retry_after() {
+        let policy = RetryPolicy::new(2).base_delay(Duration::from_millis(1));
+        let response =
+            FakeResponse::new(StatusCode::SERVICE_UNAVAILABLE).header("retry-after", "1");
+        let elapsed = timed(1, response, policy);
+        assert!(elapsed >= Duration::from_secs(1), "{elapsed:?}");
+    }
+
+    #[test]
+    fn retry_after_clamped() {
+        let policy = RetryPolicy::new(2)
+            .base_delay(Duration::from_millis(1))
+            .max_delay(Duration::from_millis(100));
+        let response =
+            FakeResponse::new(StatusCode::SERVICE_UNAVAILABLE).header("retry-after", "60");
+        let elapsed = timed(1, response, policy);
+        assert!(elapsed >= Duration::from_millis(100), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+    }
+}
+
+mod priority {
+    use std::{
+        thread::{sleep, spawn, JoinHandle},
+        time::Instant,
+    };
+
+    use super::*;
+
+    /// a client sending one request per 100ms
+    fn slow_client(api: &FakeApi) -> Client<NoopCache, BucketRateLimiter, HttpConnector, false> {
+        api.fixed(&Build { id: 1 });
+        api.bulk(&worlds("World"));
+        Client::empty()
+            .host_http(api.url())
+            .rate_limiter(BucketRateLimiter::new(1, 600))
+    }
+
+    fn spawn_world(
+        client: &Client<NoopCache, BucketRateLimiter, HttpConnector, false>,
+        priority: Priority,
+        id: WorldId,
+    ) -> JoinHandle<()> {
+        let client = client.clone();
+        let handle = spawn(move || {
+            let _: World = client.priority(priority).single(id).unwrap();
+        });
+        sleep(Duration::from_millis(10));
+        handle
+    }
+
+    fn build_position(api: &FakeApi) -> usize {
+        api.requests()
+            .iter()
+            .position(|x| x.contains("v2/build"))
+            .unwrap()
+    }
+
+    #[test]
+    fn high_first() {
+        let api = FakeApi::start();
+        let client = slow_client(&api);
+
+        let low: Vec<_> = (1001..=1003)
+            .map(|id| spawn_world(&client, Priority::Low, id))
+            .collect();
+        let _: Build = client.priority(Priority::High).get().unwrap();
+        low.into_iter().for_each(|x| x.join().unwrap());
+
+        // the first two were already on their way
+        assert_eq!(build_position(&api), 2);
+        assert_eq!(api.requests().len(), 4);
+    }
+
+    #[test]
+    fn identifiers_take_turns() {
+        let api = FakeApi::start();
+        let mut worlds = worlds("World");
+        worlds.push(World {
+            id: 1004,
+            name: "World 1004".to_string(),
+            population: PopulationLevel::High,
+        });
+        let client = slow_client(&api);
+        api.bulk(&worlds);
+
+        let busy = client.clone().identifier("busy");
+        let busy: Vec<_> = (1001..=1004)
+            .map(|id| spawn_world(&busy, Priority::Normal, id))
+            .collect();
+        let _: Build = client.identifier("other").get().unwrap();
+        busy.into_iter().for_each(|x| x.join().unwrap());
+
+        // only the request of busy already waiting for the rate limiter is ahead
+        assert_eq!(build_position(&api), 2);
+        assert_eq!(api.requests().len(), 5);
+    }
+
+    #[test]
+    fn throttled_identifier() {
+        let api = FakeApi::start();
+        api.api_key("a");
+        api.api_key("b");
+        api.fixed::<AccountMaterials>(&vec![]);
+        let client = Client::empty()
+            .host_http(api.url())
+            .rate_limiter(BucketRateLimiter::new(300, 300).per_identifier(1, 6));
+
+        let throttled = client.clone().api_key("a");
+        let _: AccountMaterials = throttled.get().unwrap();
+        // waits 10s for the bucket of a to refill
+        spawn(move || {
+            let _: Result<AccountMaterials, _> = throttled.get();
+        });
+        sleep(Duration::from_millis(10));
+
+        let start = Instant::now();
+        let _: AccountMaterials = client.api_key("b").get().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
+
+mod timeout {
+    use std::time::Instant;
+
+    use super::*;
+
+    fn slow_build(api: &FakeApi) {
+        api.fixed(&Build { id: 1 });
+        api.respond_with(
+            "v2/build",
+            FakeResponse::json(StatusCode::OK, Build { id: 2 }).delay(Duration::from_millis(500)),
+        );
+    }
+
+    #[test]
+    fn attempt() {
+        let api = FakeApi::start();
+        slow_build(&api);
+        let client = Client::empty()
+            .host_http(api.url())
+            .timeout(Duration::from_millis(100));
+
+        let res: Result<Build, _> = client.get();
+        assert!(matches!(res, Err(EndpointError::Timeout)));
+    }
+
+    #[test]
+    fn retried() {
+        let api = FakeApi::start();
+        slow_build(&api);
+        let client = client(&api).timeout(Duration::from_millis(100));
+
+        let build: Build = client.get().unwrap();
+        assert_eq!(build.id, 1);
+        assert_eq!(api.requests().len(), 2);
+    }
+
+    #[test]
+    fn disabled() {
+        let api = FakeApi::start();
+        slow_build(&api);
+        let client = client(&api)
+            .timeout(Duration::from_millis(100))
+            .timeout(None)
+            .connect_timeout(Duration::from_secs(1));
+
+        let build: Build = client.get().unwrap();
+        assert_eq!(build.id, 2);
+        assert_eq!(api.requests().len(), 1);
+    }
+
+    #[test]
+    fn deadline() {
+        let api = FakeApi::start();
+        slow_build(&api);
+        let client = Client::empty().host_http(api.url());
+
+        let start = Instant::now();
+        let res: Result<Build, _> = client.deadline(Duration::from_millis(100)).get();
+        assert!(matches!(res, Err(EndpointError::Timeout)));
+        assert!(start.elapsed() < Duration::from_millis(400));
+    }
+
+    #[test]
+    fn wakes_waiters() {
+        let api = FakeApi::start();
+        slow_build(&api);
+        let client = Client::empty().host_http(api.url());
+
+        let leader = client.clone();
+        let leader = std::thread::spawn(move || {
+            let res: Result<Build, _> = leader.deadline(Duration::from_millis(100)).get();
+            res
+        });
+        std::thread::sleep(Duration::from_millis(20));
+        let start = Instant::now();
+        let res: Result<Build, _> = client.get();
+
+        assert!(matches!(res, Err(EndpointError::Timeout)));
+        assert!(start.elapsed() < Duration::from_millis(400));
+        assert!(matches!(
+            leader.join().unwrap(),
+            Err(EndpointError::Timeout)
+        ));
+        assert_eq!(api.requests().len(), 1);
+    }
+}
+
+mod language {
+    use super::*;
+
+    fn api() -> FakeApi {
+        let api = FakeApi::start();
+        api.bulk(&worlds("World"));
+        api.bulk_in(Language::De, &worlds("Welt"));
+        api.bulk_in(Language::Fr, &worlds("Monde"));
+        api
+    }
+
+    #[test]
+    fn lang() {
+        let api = api();
+        let client = client(&api);
+
+        let world: World = client.lang(Language::De).single(1001).unwrap();
+        assert_eq!(world.name, "Welt 1001");
+        let world: World = client.single(1001).unwrap();
+        assert_eq!(world.name, "World 1001");
+        // cached per language
+        let world: World = client.lang(Language::De).single(1001).unwrap();
+        assert_eq!(world.name, "Welt 1001");
+        assert_eq!(api.requests().len(), 2);
+    }
+
+    #[test]
+    fn localized() {
+        let api = api();
+        let client = client(&api);
+
+        let worlds = client.localized::<World, WorldId>(1001).unwrap();
+        assert_eq!(worlds.len(), Language::ALL.len());
+        assert_eq!(worlds[&Language::En].name, "World 1001");
+        assert_eq!(worlds[&Language::De].name, "Welt 1001");
+        assert_eq!(worlds[&Language::Fr].name, "Monde 1001");
+
+        let world: World = client.lang(Language::Fr).single(1001).unwrap();
+        assert_eq!(world.name, "Monde 1001");
+        assert_eq!(api.requests().len(), Language::ALL.len());
+    }
+}
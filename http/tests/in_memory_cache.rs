@@ -0,0 +1,236 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use gw2lib::{
+    cache::{Cache, CacheLimits, Eviction, InMemoryCache},
+    model::{
+        authenticated::account::materials::AccountMaterials,
+        maps::continents::{Continent, Floor},
+        misc::{
+            build::Build,
+            worlds::{PopulationLevel, World},
+        },
+        Language,
+    },
+};
+
+fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(fut)
+}
+
+fn expiring() -> NaiveDateTime {
+    Utc::now().naive_utc() + Duration::minutes(5)
+}
+
+fn insert_world(cache: &InMemoryCache, id: u16) {
+    let world = World {
+        id,
+        name: format!("World {id}"),
+        population: PopulationLevel::High,
+    };
+    block_on(cache.insert::<World, u16, World, String>(
+        &id,
+        &world,
+        expiring(),
+        Language::En,
+        &None,
+    ));
+}
+
+fn get_world(cache: &InMemoryCache, id: u16) -> Option<World> {
+    block_on(cache.get::<World, u16, World, String>(&id, Language::En, &None))
+}
+
+fn insert_materials(cache: &InMemoryCache, key: &str) {
+    block_on(
+        cache.insert::<AccountMaterials, str, AccountMaterials, String>(
+            "",
+            &vec![],
+            expiring(),
+            Language::En,
+            &Some(key.to_string()),
+        ),
+    );
+}
+
+#[test]
+fn unbounded() {
+    let cache = InMemoryCache::default();
+    (1..=100).for_each(|id| insert_world(&cache, id));
+    assert_eq!(cache.len(), 100);
+}
+
+#[test]
+fn lru() {
+    let cache = InMemoryCache::default().static_limits(CacheLimits::default().max_entries(10));
+    (1..=10).for_each(|id| insert_world(&cache, id));
+    get_world(&cache, 1).unwrap();
+
+    insert_world(&cache, 11);
+    assert_eq!(cache.len(), 9);
+    assert!(get_world(&cache, 1).is_some());
+    assert!(get_world(&cache, 2).is_none());
+    assert!(get_world(&cache, 3).is_none());
+    assert!(get_world(&cache, 11).is_some());
+}
+
+#[test]
+fn lfu() {
+    let cache = InMemoryCache::default()
+        .static_limits(CacheLimits::default().max_entries(10))
+        .eviction(Eviction::Lfu);
+    (1..=10).for_each(|id| insert_world(&cache, id));
+    for id in 2..=10 {
+        get_world(&cache, id).unwrap();
+    }
+    // the most recently used entry stays despite being used least
+    get_world(&cache, 1).unwrap();
+    get_world(&cache, 1).unwrap();
+    get_world(&cache, 2).unwrap();
+
+    insert_world(&cache, 11);
+    assert_eq!(cache.len(), 9);
+    assert!(get_world(&cache, 3).is_none());
+    assert!(get_world(&cache, 4).is_none());
+    assert!(get_world(&cache, 1).is_some());
+}
+
+#[test]
+fn bytes() {
+    let cache = InMemoryCache::default().static_limits(CacheLimits::default().max_bytes(4096));
+    (1..=1000).for_each(|id| insert_world(&cache, id));
+    assert!(cache.len() < 100);
+    assert!(get_world(&cache, 1000).is_some());
+}
+
+#[test]
+fn endpoint() {
+    let cache = InMemoryCache::default().endpoint_limit::<World>(3);
+    block_on(cache.insert::<Build, str, Build, String>(
+        "",
+        &Build { id: 1 },
+        expiring(),
+        Language::En,
+        &None,
+    ));
+    (1..=5).for_each(|id| insert_world(&cache, id));
+
+    assert_eq!(cache.len(), 4);
+    assert!(get_world(&cache, 5).is_some());
+    assert!(get_world(&cache, 1).is_none());
+}
+
+#[test]
+fn partitions() {
+    let cache =
+        InMemoryCache::default().authenticated_limits(CacheLimits::default().max_entries(2));
+    (1..=5).for_each(|id| insert_world(&cache, id));
+    for key in ["a", "b", "c"] {
+        insert_materials(&cache, key);
+    }
+    assert_eq!(cache.len(), 7);
+
+    block_on(cache.wipe_authenticated());
+    assert_eq!(cache.len(), 5);
+}
+
+fn get_materials(cache: &InMemoryCache, key: &str) -> Option<AccountMaterials> {
+    block_on(
+        cache.get::<AccountMaterials, str, AccountMaterials, String>(
+            "",
+            Language::En,
+            &Some(key.to_string()),
+        ),
+    )
+}
+
+#[test]
+fn remove() {
+    let cache = InMemoryCache::default();
+    (1..=3).for_each(|id| insert_world(&cache, id));
+
+    block_on(cache.remove::<World, u16, World, String>(&2, Language::En, &None));
+    assert!(get_world(&cache, 1).is_some());
+    assert!(get_world(&cache, 2).is_none());
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn wipe_endpoint() {
+    let cache = InMemoryCache::default();
+    (1..=3).for_each(|id| insert_world(&cache, id));
+    block_on(cache.insert::<Build, str, Build, String>(
+        "",
+        &Build { id: 1 },
+        expiring(),
+        Language::En,
+        &None,
+    ));
+
+    block_on(cache.wipe_endpoint::<World>());
+    assert_eq!(cache.len(), 1);
+    assert!(get_world(&cache, 1).is_none());
+}
+
+#[test]
+fn wipe_endpoint_sharing_url() {
+    let cache = InMemoryCache::default();
+    let build = Build { id: 1 };
+    block_on(cache.insert::<Build, str, Continent, String>(
+        "1",
+        &build,
+        expiring(),
+        Language::En,
+        &None,
+    ));
+    block_on(cache.insert::<Build, str, Floor, String>(
+        "1/floors/1",
+        &build,
+        expiring(),
+        Language::En,
+        &None,
+    ));
+
+    block_on(cache.wipe_endpoint::<Floor>());
+    assert_eq!(cache.len(), 1);
+    let continent = block_on(cache.get::<Build, str, Continent, String>("1", Language::En, &None));
+    assert!(continent.is_some());
+}
+
+#[test]
+fn wipe_identifier() {
+    let cache = InMemoryCache::default();
+    insert_world(&cache, 1);
+    insert_materials(&cache, "a");
+    insert_materials(&cache, "b");
+
+    block_on(cache.wipe_identifier(&"a".to_string()));
+    assert!(get_materials(&cache, "a").is_none());
+    assert!(get_materials(&cache, "b").is_some());
+    assert!(get_world(&cache, 1).is_some());
+}
+
+#[test]
+fn many() {
+    let cache = InMemoryCache::default();
+    let worlds: Vec<_> = (1..=3)
+        .map(|id| World {
+            id,
+            name: format!("World {id}"),
+            population: PopulationLevel::High,
+        })
+        .collect();
+    let entries: Vec<_> = worlds.iter().map(|x| (&x.id, x)).collect();
+    block_on(cache.insert_many::<World, u16, World, String>(
+        &entries,
+        expiring(),
+        Language::En,
+        &None,
+    ));
+
+    let found =
+        block_on(cache.get_many::<World, u16, World, String>(&[3, 4, 1], Language::En, &None));
+    let found: Vec<_> = found.into_iter().map(|x| x.map(|x| x.id)).collect();
+    assert_eq!(found, [Some(3), None, Some(1)]);
+}
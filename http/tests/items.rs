@@ -36,12 +36,12 @@ fn parse_all() {
         client.cache(cache)
     };
     let res: Result<Vec<Item>, _> = client.all();
-    if let Err(EndpointError::InvalidJsonResponse(_)) = res {
+    if let Err(EndpointError::InvalidJsonResponse { .. }) = res {
         let ids = client.ids::<Item, ItemId>().unwrap();
         for chunk in ids.chunks(200) {
             let res: Result<Vec<Item>, _> = client.many(chunk.to_vec());
             match res {
-                Err(EndpointError::InvalidJsonResponse(_)) => {
+                Err(EndpointError::InvalidJsonResponse { .. }) => {
                     for &id in chunk {
                         let _: Item = client.single(id).map_err(|e| (id, e)).unwrap();
                     }
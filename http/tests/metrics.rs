@@ -0,0 +1,127 @@
+#![cfg(all(feature = "blocking", feature = "metrics", feature = "testing"))]
+
+use std::time::Duration;
+
+use gw2lib::{
+    model::{
+        maps::continents::Floor,
+        misc::{
+            build::Build,
+            worlds::{PopulationLevel, World},
+        },
+    },
+    retry::RetryPolicy,
+    testing::{FakeApi, FakeResponse},
+    Client, Requester,
+};
+use hyper::StatusCode;
+
+fn worlds() -> Vec<World> {
+    (1001..=1003)
+        .map(|id| World {
+            id,
+            name: format!("World {id}"),
+            population: PopulationLevel::High,
+        })
+        .collect()
+}
+
+#[test]
+fn cache_lookups() {
+    let api = FakeApi::start();
+    api.fixed(&Build { id: 1 });
+    let client = Client::default().host_http(api.url());
+
+    let _: Build = client.get().unwrap();
+    let _: Build = client.get().unwrap();
+    let _: Build = client.forced().get().unwrap();
+
+    let stats = client.stats();
+    let build = &stats.endpoints["v2/build"];
+    assert_eq!(build.cache_hits, 1);
+    assert_eq!(build.cache_misses, 1);
+    assert_eq!(build.hit_ratio(), Some(0.5));
+    assert_eq!(build.upstream_requests, 2);
+    assert_eq!(build.status_codes[&200], 2);
+    assert_eq!(build.latency.count, 2);
+    assert_eq!(stats.rate_limit_wait.count, 2);
+}
+
+#[test]
+fn many() {
+    let api = FakeApi::start();
+    api.bulk(&worlds());
+    let client = Client::default().host_http(api.url());
+
+    let _: World = client.single(1001).unwrap();
+    let _: Vec<World> = client.many(vec![1001, 1002, 1003]).unwrap();
+
+    let worlds = &client.stats().endpoints["v2/worlds"];
+    assert_eq!(worlds.cache_hits, 1);
+    assert_eq!(worlds.cache_misses, 3);
+    assert_eq!(worlds.upstream_requests, 2);
+}
+
+#[test]
+fn endpoints_sharing_url() {
+    let api = FakeApi::start();
+    api.raw("v2/continents/1/floors/1", serde_json::json!({}));
+    let client = Client::default().host_http(api.url());
+
+    let _ = client.single::<Floor, _>((1, 1).into());
+
+    let endpoints = client.stats().endpoints;
+    assert_eq!(endpoints["v2/continents/:id/floors"].upstream_requests, 1);
+    assert!(!endpoints.contains_key("v2/continents"));
+}
+
+#[test]
+fn raw_per_path() {
+    let api = FakeApi::start();
+    api.raw("v2/wizardsvault", serde_json::json!({}));
+    api.raw("v2/wizardsvault/daily", serde_json::json!({}));
+    let client = Client::default().host_http(api.url());
+
+    let _ = client.raw("v2/wizardsvault", &()).unwrap();
+    let _ = client.raw("v2/wizardsvault", &()).unwrap();
+    let _ = client.raw("/v2/wizardsvault/daily", &()).unwrap();
+
+    let endpoints = client.stats().endpoints;
+    assert_eq!(endpoints["v2/wizardsvault"].cache_hits, 1);
+    assert_eq!(endpoints["v2/wizardsvault"].upstream_requests, 1);
+    assert_eq!(endpoints["v2/wizardsvault/daily"].upstream_requests, 1);
+    assert!(!endpoints.contains_key("raw"));
+}
+
+#[test]
+fn retries() {
+    let api = FakeApi::start();
+    api.fixed(&Build { id: 1 });
+    api.respond_with(
+        "v2/build",
+        FakeResponse::new(StatusCode::SERVICE_UNAVAILABLE),
+    );
+    let client = Client::empty()
+        .host_http(api.url())
+        .retry(RetryPolicy::new(2).base_delay(Duration::from_millis(1)));
+
+    let _: Build = client.get().unwrap();
+
+    let build = &client.stats().endpoints["v2/build"];
+    assert_eq!(build.upstream_requests, 2);
+    assert_eq!(build.status_codes[&503], 1);
+    assert_eq!(build.status_codes[&200], 1);
+}
+
+#[test]
+fn shared_between_clients() {
+    let api = FakeApi::start();
+    api.fixed(&Build { id: 1 });
+    let client = Client::default().host_http(api.url());
+    let other = client.clone().language(gw2lib::model::Language::De);
+
+    let _: Build = client.get().unwrap();
+    let _: Build = other.get().unwrap();
+
+    assert_eq!(client.stats().endpoints["v2/build"].cache_hits, 1);
+}
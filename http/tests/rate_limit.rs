@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use gw2lib::rate_limit::{BucketRateLimiter, RateLimiter, RequestContext};
+
+fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(fut)
+}
+
+fn take(limiter: &BucketRateLimiter, identifier: Option<&str>) -> Duration {
+    let ctx = RequestContext {
+        identifier,
+        endpoint: "v2/account",
+    };
+    block_on(limiter.take(1, &ctx)).unwrap()
+}
+
+#[test]
+fn global() {
+    let limiter = BucketRateLimiter::new(2, 60);
+    assert_eq!(take(&limiter, None), Duration::ZERO);
+    assert_eq!(take(&limiter, Some("a")), Duration::ZERO);
+    assert!(take(&limiter, Some("b")) > Duration::ZERO);
+}
+
+#[test]
+fn per_identifier() {
+    let limiter = BucketRateLimiter::new(300, 300).per_identifier(2, 60);
+    assert_eq!(take(&limiter, Some("a")), Duration::ZERO);
+    assert_eq!(take(&limiter, Some("a")), Duration::ZERO);
+    assert!(take(&limiter, Some("a")) > Duration::from_millis(500));
+
+    // other keys and unauthenticated requests only share the global bucket
+    assert_eq!(take(&limiter, Some("b")), Duration::ZERO);
+    assert_eq!(take(&limiter, None), Duration::ZERO);
+}
+
+#[test]
+fn shared_global_bucket() {
+    let limiter = BucketRateLimiter::new(2, 60).per_identifier(10, 600);
+    assert_eq!(take(&limiter, Some("a")), Duration::ZERO);
+    assert_eq!(take(&limiter, Some("a")), Duration::ZERO);
+    assert!(take(&limiter, Some("b")) > Duration::from_millis(500));
+}
+
+#[test]
+fn exceeded() {
+    let limiter = BucketRateLimiter::new(300, 300).per_identifier(2, 60);
+    let ctx = RequestContext {
+        identifier: Some("a"),
+        endpoint: "v2/account",
+    };
+    assert!(block_on(limiter.take(3, &ctx)).is_err());
+    assert!(block_on(limiter.take(3, &RequestContext::default())).is_ok());
+}
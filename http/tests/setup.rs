@@ -16,7 +16,7 @@ pub fn setup() -> Client<InMemoryCache, BucketRateLimiter, HttpsConnector<HttpCo
         .ok()
         .and_then(|x| (!x.is_empty()).then_some(x))
         .unwrap_or(API_KEY.into());
-    Client::default().api_key(apikey)
+    cassette(Client::default().api_key(apikey))
 }
 
 #[cfg(feature = "redis")]
@@ -27,7 +27,43 @@ pub fn setup() -> Client<InMemoryCache, RedisRateLimiter, HttpsConnector<HttpCon
         .unwrap_or(API_KEY.into());
     let client = redis::Client::open("redis://localhost").unwrap();
     let rate_limiter = RedisRateLimiter::new(client).unwrap();
-    Client::default().api_key(apikey).rate_limiter(rate_limiter)
+    cassette(Client::default().api_key(apikey).rate_limiter(rate_limiter))
+}
+
+/// records into or replays from `$GW2LIB_CASSETTES/<test>.json` when set
+///
+/// Recording happens when `GW2LIB_RECORD` is set as well.
+#[cfg(feature = "testing")]
+fn cassette<C, R, Conn>(client: Client<C, R, Conn, true>) -> Client<C, R, Conn, true>
+where
+    C: gw2lib::cache::Cache + Send + Sync + 'static,
+    R: gw2lib::rate_limit::RateLimiter + Send + Sync + 'static,
+    Conn: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
+{
+    use std::sync::{Arc, OnceLock};
+
+    use gw2lib::testing::Cassette;
+
+    static CASSETTE: OnceLock<Option<Arc<Cassette>>> = OnceLock::new();
+    let cassette = CASSETTE.get_or_init(|| {
+        let dir = std::env::var_os("GW2LIB_CASSETTES")?;
+        let path = std::path::Path::new(&dir).join(concat!(env!("CARGO_CRATE_NAME"), ".json"));
+        let cassette = if std::env::var_os("GW2LIB_RECORD").is_some() {
+            Cassette::record(path)
+        } else {
+            Cassette::replay(path).expect("failed to load cassette")
+        };
+        Some(Arc::new(cassette))
+    });
+    match cassette {
+        Some(cassette) => client.cassette(cassette.clone()),
+        None => client,
+    }
+}
+
+#[cfg(not(feature = "testing"))]
+fn cassette<T>(client: T) -> T {
+    client
 }
 
 pub fn character_name() -> String {
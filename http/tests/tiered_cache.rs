@@ -0,0 +1,100 @@
+#![cfg(all(feature = "blocking", feature = "testing"))]
+
+use std::sync::Arc;
+
+use gw2lib::{
+    cache::{Cache, InMemoryCache, TieredCache},
+    model::misc::build::Build,
+    rate_limit::NoopRateLimiter,
+    testing::FakeApi,
+    Client, Requester,
+};
+use hyper::client::HttpConnector;
+
+type Tiered = TieredCache<InMemoryCache, Arc<InMemoryCache>>;
+
+fn client(
+    api: &FakeApi,
+    shared: &Arc<InMemoryCache>,
+) -> (
+    Arc<Tiered>,
+    Client<Tiered, NoopRateLimiter, HttpConnector, false>,
+) {
+    let cache = Arc::new(TieredCache::new(InMemoryCache::default(), shared.clone()));
+    let client = Client::empty().host_http(api.url()).cache(cache.clone());
+    (cache, client)
+}
+
+fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(fut)
+}
+
+#[test]
+fn shared_hit() {
+    let api = FakeApi::start();
+    api.fixed(&Build { id: 1 });
+    let shared = Arc::new(InMemoryCache::default());
+    let (_, first) = client(&api, &shared);
+    let (_, second) = client(&api, &shared);
+
+    let _: Build = first.get().unwrap();
+    let build: Build = second.get().unwrap();
+    assert_eq!(build.id, 1);
+    assert_eq!(api.requests().len(), 1);
+}
+
+#[test]
+fn promotes() {
+    let api = FakeApi::start();
+    api.fixed(&Build { id: 1 });
+    let shared = Arc::new(InMemoryCache::default());
+    let (_, first) = client(&api, &shared);
+    let (second_cache, second) = client(&api, &shared);
+
+    let _: Build = first.get().unwrap();
+    let _: Build = second.get().unwrap();
+    // only the shared layer is gone, the local copy still hits
+    block_on(shared.wipe());
+    let _: Build = second.get().unwrap();
+    assert_eq!(api.requests().len(), 1);
+
+    block_on(second_cache.l1().wipe());
+    let _: Build = second.get().unwrap();
+    assert_eq!(api.requests().len(), 2);
+}
+
+#[test]
+fn wipe() {
+    let api = FakeApi::start();
+    api.fixed(&Build { id: 1 });
+    let shared = Arc::new(InMemoryCache::default());
+    let (cache, client) = client(&api, &shared);
+
+    let _: Build = client.get().unwrap();
+    block_on(cache.wipe_static());
+    let _: Build = client.get().unwrap();
+    assert_eq!(api.requests().len(), 2);
+}
+
+#[test]
+fn newer_shared_entry() {
+    let api = FakeApi::start();
+    api.fixed(&Build { id: 1 });
+    api.max_age(0);
+    let shared = Arc::new(InMemoryCache::default());
+    let (_, first) = client(&api, &shared);
+    let (_, second) = client(&api, &shared);
+
+    let _: Build = first.get().unwrap();
+    api.max_age(300);
+    api.fixed(&Build { id: 2 });
+    let _: Build = second.get().unwrap();
+    // the expired local entry is replaced by the one refreshed by `second`
+    let build: Build = first.get().unwrap();
+    assert_eq!(build.id, 2);
+    assert_eq!(api.requests().len(), 2);
+}
@@ -10,7 +10,7 @@ use crate::{
     misc::{colors::ColorId, titles::TitleId},
     pvp::amulets::AmuletId,
     wvw::abilities::AbilityId,
-    BulkEndpoint, Endpoint, EndpointWithId, TimeStamp,
+    BulkEndpoint, Endpoint, EndpointWithId, ParentBulkEndpoint, ParentEndpoint, TimeStamp,
 };
 
 pub type Age = u64;
@@ -376,6 +376,56 @@ pub struct Character {
     pub flags: Vec<Flags>,
 }
 
+impl Endpoint for BuildTab {
+    const AUTHENTICATED: bool = true;
+    const LOCALE: bool = false;
+    const NAME: &'static str = "v2/characters/:id/buildtabs";
+    const URL: &'static str = "v2/characters";
+    const VERSION: &'static str = "2022-06-14T00:00:00.000Z";
+}
+
+impl ParentEndpoint for BuildTab {
+    type ParentId = CharacterId;
+
+    const PATH: &'static str = "buildtabs";
+}
+
+impl ParentBulkEndpoint for BuildTab {
+    type IdType = usize;
+
+    const ALL: bool = true;
+    const IDS: &'static str = "tabs";
+
+    fn id(&self) -> &Self::IdType {
+        &self.tab
+    }
+}
+
+impl Endpoint for EquipmentTab {
+    const AUTHENTICATED: bool = true;
+    const LOCALE: bool = false;
+    const NAME: &'static str = "v2/characters/:id/equipmenttabs";
+    const URL: &'static str = "v2/characters";
+    const VERSION: &'static str = "2022-06-14T00:00:00.000Z";
+}
+
+impl ParentEndpoint for EquipmentTab {
+    type ParentId = CharacterId;
+
+    const PATH: &'static str = "equipmenttabs";
+}
+
+impl ParentBulkEndpoint for EquipmentTab {
+    type IdType = usize;
+
+    const ALL: bool = true;
+    const IDS: &'static str = "tabs";
+
+    fn id(&self) -> &Self::IdType {
+        &self.tab
+    }
+}
+
 impl EndpointWithId for Character {
     type IdType = CharacterId;
 }
@@ -399,13 +449,20 @@ impl EndpointWithId for Core {
     type IdType = CharacterId;
 
     fn format_url(id: &str) -> String {
-        format!("{}/{}/core", Self::URL, id)
+        Self::parent_url(id)
     }
 }
 
+impl ParentEndpoint for Core {
+    type ParentId = CharacterId;
+
+    const PATH: &'static str = "core";
+}
+
 impl Endpoint for Core {
     const AUTHENTICATED: bool = true;
     const LOCALE: bool = false;
+    const NAME: &'static str = "v2/characters/:id/core";
     const URL: &'static str = "v2/characters";
     const VERSION: &'static str = "2022-06-14T00:00:00.000Z";
 }
@@ -414,13 +471,20 @@ impl EndpointWithId for Backstory {
     type IdType = CharacterId;
 
     fn format_url(id: &str) -> String {
-        format!("{}/{}/backstory", Self::URL, id)
+        Self::parent_url(id)
     }
 }
 
+impl ParentEndpoint for Backstory {
+    type ParentId = CharacterId;
+
+    const PATH: &'static str = "backstory";
+}
+
 impl Endpoint for Backstory {
     const AUTHENTICATED: bool = true;
     const LOCALE: bool = false;
+    const NAME: &'static str = "v2/characters/:id/backstory";
     const URL: &'static str = "v2/characters";
     const VERSION: &'static str = "2022-06-14T00:00:00.000Z";
 }
@@ -429,13 +493,20 @@ impl EndpointWithId for Crafting {
     type IdType = CharacterId;
 
     fn format_url(id: &str) -> String {
-        format!("{}/{}/crafting", Self::URL, id)
+        Self::parent_url(id)
     }
 }
 
+impl ParentEndpoint for Crafting {
+    type ParentId = CharacterId;
+
+    const PATH: &'static str = "crafting";
+}
+
 impl Endpoint for Crafting {
     const AUTHENTICATED: bool = true;
     const LOCALE: bool = false;
+    const NAME: &'static str = "v2/characters/:id/crafting";
     const URL: &'static str = "v2/characters";
     const VERSION: &'static str = "2022-06-14T00:00:00.000Z";
 }
@@ -444,13 +515,20 @@ impl EndpointWithId for Equipment {
     type IdType = CharacterId;
 
     fn format_url(id: &str) -> String {
-        format!("{}/{}/equipment", Self::URL, id)
+        Self::parent_url(id)
     }
 }
 
+impl ParentEndpoint for Equipment {
+    type ParentId = CharacterId;
+
+    const PATH: &'static str = "equipment";
+}
+
 impl Endpoint for Equipment {
     const AUTHENTICATED: bool = true;
     const LOCALE: bool = false;
+    const NAME: &'static str = "v2/characters/:id/equipment";
     const URL: &'static str = "v2/characters";
     const VERSION: &'static str = "2022-06-14T00:00:00.000Z";
 }
@@ -459,13 +537,20 @@ impl EndpointWithId for Inventory {
     type IdType = CharacterId;
 
     fn format_url(id: &str) -> String {
-        format!("{}/{}/inventory", Self::URL, id)
+        Self::parent_url(id)
     }
 }
 
+impl ParentEndpoint for Inventory {
+    type ParentId = CharacterId;
+
+    const PATH: &'static str = "inventory";
+}
+
 impl Endpoint for Inventory {
     const AUTHENTICATED: bool = true;
     const LOCALE: bool = false;
+    const NAME: &'static str = "v2/characters/:id/inventory";
     const URL: &'static str = "v2/characters";
     const VERSION: &'static str = "2022-06-14T00:00:00.000Z";
 }
@@ -474,13 +559,20 @@ impl EndpointWithId for Recipes {
     type IdType = CharacterId;
 
     fn format_url(id: &str) -> String {
-        format!("{}/{}/recipes", Self::URL, id)
+        Self::parent_url(id)
     }
 }
 
+impl ParentEndpoint for Recipes {
+    type ParentId = CharacterId;
+
+    const PATH: &'static str = "recipes";
+}
+
 impl Endpoint for Recipes {
     const AUTHENTICATED: bool = true;
     const LOCALE: bool = false;
+    const NAME: &'static str = "v2/characters/:id/recipes";
     const URL: &'static str = "v2/characters";
     const VERSION: &'static str = "2022-06-14T00:00:00.000Z";
 }
@@ -489,13 +581,20 @@ impl EndpointWithId for Training {
     type IdType = CharacterId;
 
     fn format_url(id: &str) -> String {
-        format!("{}/{}/training", Self::URL, id)
+        Self::parent_url(id)
     }
 }
 
+impl ParentEndpoint for Training {
+    type ParentId = CharacterId;
+
+    const PATH: &'static str = "training";
+}
+
 impl Endpoint for Training {
     const AUTHENTICATED: bool = true;
     const LOCALE: bool = false;
+    const NAME: &'static str = "v2/characters/:id/training";
     const URL: &'static str = "v2/characters";
     const VERSION: &'static str = "2022-06-14T00:00:00.000Z";
 }
@@ -0,0 +1,107 @@
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{items::ItemId, Endpoint, PagedEndpoint, TimeStamp};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub struct Transaction {
+    pub id: u64,
+    pub item_id: ItemId,
+    /// price per item in coins
+    pub price: u64,
+    pub quantity: u64,
+    pub created: TimeStamp,
+    /// only set for completed transactions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purchased: Option<TimeStamp>,
+}
+
+/// a buy order that has not been fulfilled yet
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct CurrentBuy(pub Transaction);
+
+impl Endpoint for CurrentBuy {
+    const AUTHENTICATED: bool = true;
+    const LOCALE: bool = false;
+    const URL: &'static str = "v2/commerce/transactions/current/buys";
+    const VERSION: &'static str = "2023-07-01T00:00:00.000Z";
+}
+
+impl PagedEndpoint for CurrentBuy {}
+
+impl Deref for CurrentBuy {
+    type Target = Transaction;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// a sell listing that has not been bought yet
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct CurrentSell(pub Transaction);
+
+impl Endpoint for CurrentSell {
+    const AUTHENTICATED: bool = true;
+    const LOCALE: bool = false;
+    const URL: &'static str = "v2/commerce/transactions/current/sells";
+    const VERSION: &'static str = "2023-07-01T00:00:00.000Z";
+}
+
+impl PagedEndpoint for CurrentSell {}
+
+impl Deref for CurrentSell {
+    type Target = Transaction;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// a fulfilled buy order of the past 90 days
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct HistoryBuy(pub Transaction);
+
+impl Endpoint for HistoryBuy {
+    const AUTHENTICATED: bool = true;
+    const LOCALE: bool = false;
+    const URL: &'static str = "v2/commerce/transactions/history/buys";
+    const VERSION: &'static str = "2023-07-01T00:00:00.000Z";
+}
+
+impl PagedEndpoint for HistoryBuy {}
+
+impl Deref for HistoryBuy {
+    type Target = Transaction;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// a sold listing of the past 90 days
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct HistorySell(pub Transaction);
+
+impl Endpoint for HistorySell {
+    const AUTHENTICATED: bool = true;
+    const LOCALE: bool = false;
+    const URL: &'static str = "v2/commerce/transactions/history/sells";
+    const VERSION: &'static str = "2023-07-01T00:00:00.000Z";
+}
+
+impl PagedEndpoint for HistorySell {}
+
+impl Deref for HistorySell {
+    type Target = Transaction;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -1 +1,5 @@
+pub mod members;
+pub mod search;
 pub mod upgrades;
+
+pub type GuildId = String;
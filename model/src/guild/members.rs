@@ -0,0 +1,44 @@
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{guild::GuildId, Endpoint, ParentEndpoint, TimeStamp};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub struct GuildMember {
+    /// the account name of the member
+    pub name: String,
+    pub rank: String,
+    /// not set for members that joined before this got tracked
+    pub joined: Option<TimeStamp>,
+    #[serde(default)]
+    pub wvw_member: bool,
+}
+
+/// the members of a guild, requires the api key of the guild leader
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GuildMembers(pub Vec<GuildMember>);
+
+impl Endpoint for GuildMembers {
+    const AUTHENTICATED: bool = true;
+    const LOCALE: bool = false;
+    const NAME: &'static str = "v2/guild/:id/members";
+    const URL: &'static str = "v2/guild";
+    const VERSION: &'static str = "2023-07-01T00:00:00.000Z";
+}
+
+impl ParentEndpoint for GuildMembers {
+    type ParentId = GuildId;
+
+    const PATH: &'static str = "members";
+}
+
+impl Deref for GuildMembers {
+    type Target = Vec<GuildMember>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{guild::GuildId, Endpoint, QueryEndpoint};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GuildSearchQuery {
+    /// the exact name of the guild, case insensitive
+    pub name: String,
+}
+
+/// the ids of the guilds named like [`GuildSearchQuery::name`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GuildSearch(pub Vec<GuildId>);
+
+impl Endpoint for GuildSearch {
+    const AUTHENTICATED: bool = false;
+    const LOCALE: bool = false;
+    const URL: &'static str = "v2/guild/search";
+    const VERSION: &'static str = "2023-07-01T00:00:00.000Z";
+}
+
+impl QueryEndpoint for GuildSearch {
+    type Query = GuildSearchQuery;
+}
@@ -30,6 +30,15 @@ impl Display for Language {
 }
 
 impl Language {
+    /// every language the api supports
+    pub const ALL: [Language; 5] = [
+        Language::En,
+        Language::Fr,
+        Language::De,
+        Language::Es,
+        Language::Zh,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Language::En => "en",
@@ -76,6 +85,14 @@ pub trait Endpoint: Sized {
     /// of single items here: [EndpointWithId::format_url]
     const URL: &'static str;
 
+    /// unique name of the endpoint, used to tell endpoints apart in caches,
+    /// limits and metrics
+    ///
+    /// Defaults to [`Self::URL`]. Endpoints sharing their url with others,
+    /// like the ones below a [`ParentEndpoint`], set it in the format
+    /// `v2/characters/:id/core`.
+    const NAME: &'static str = Self::URL;
+
     /// version of the endpoint to request
     const VERSION: &'static str;
 }
@@ -103,4 +120,51 @@ pub trait BulkEndpoint: EndpointWithId {
 
 pub trait PagedEndpoint: Endpoint {}
 
+/// an endpoint below a parent resource, like `v2/guild/:id/members`
+///
+/// [`Endpoint::URL`] is the url of the parent, e.g. `v2/guild`, so
+/// [`Endpoint::NAME`] has to be set if other endpoints share it
+pub trait ParentEndpoint: Endpoint {
+    /// the id of the parent, e.g. a guild or character
+    type ParentId: Display;
+
+    /// the path below the parent, e.g. `members`
+    const PATH: &'static str;
+
+    fn format_parent(id: &Self::ParentId) -> String {
+        urlencoding::encode(&id.to_string()).into_owned()
+    }
+
+    /// url of the resource below `parent`, in the format
+    /// `v2/guild/<id>/members`
+    fn parent_url(parent: &str) -> String {
+        format!("{}/{}/{}", Self::URL, parent, Self::PATH)
+    }
+}
+
+/// a [`ParentEndpoint`] listing entries with their own ids, like the build
+/// tabs of a character at `v2/characters/:id/buildtabs/:tab`
+pub trait ParentBulkEndpoint: ParentEndpoint {
+    type IdType: Display;
+
+    /// whether this endpoint supports `ids=all`
+    const ALL: bool;
+
+    /// the query parameter taking multiple ids
+    const IDS: &'static str = "ids";
+
+    fn id(&self) -> &Self::IdType;
+
+    fn format_id(id: &Self::IdType) -> String {
+        urlencoding::encode(&id.to_string()).into_owned()
+    }
+}
+
+/// an endpoint answering to query parameters, like
+/// `v2/commerce/exchange/coins?quantity=100000`
+pub trait QueryEndpoint: Endpoint {
+    /// serialized into the query string, fields set to `None` are left out
+    type Query: Serialize + Send + Sync;
+}
+
 impl<T: BulkEndpoint> PagedEndpoint for T {}
@@ -10,7 +10,7 @@ use crate::{
         continents::{ContinentId, Dimensions},
         MapId,
     },
-    Endpoint, EndpointWithId,
+    Endpoint, EndpointWithId, ParentBulkEndpoint, ParentEndpoint,
 };
 
 pub type FloorId = i16;
@@ -42,6 +42,33 @@ impl Display for ContinentFloorId {
     }
 }
 
+#[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct ContinentFloorRegionId {
+    pub continent: ContinentId,
+    pub floor: FloorId,
+    pub region: RegionId,
+}
+
+impl From<(ContinentId, FloorId, RegionId)> for ContinentFloorRegionId {
+    fn from(value: (ContinentId, FloorId, RegionId)) -> Self {
+        Self {
+            continent: value.0,
+            floor: value.1,
+            region: value.2,
+        }
+    }
+}
+
+impl Display for ContinentFloorRegionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/floors/{}/regions/{}",
+            self.continent, self.floor, self.region
+        )
+    }
+}
+
 #[derive(Clone, Debug, PartialOrd, PartialEq, Serialize, Deserialize)]
 #[serde(from = "[f32; 2]", into = "[f32; 2]")]
 pub struct Coordinates {
@@ -327,6 +354,63 @@ impl EndpointWithId for Floor {
 impl Endpoint for Floor {
     const AUTHENTICATED: bool = false;
     const LOCALE: bool = true;
+    const NAME: &'static str = "v2/continents/:id/floors";
+    const URL: &'static str = "v2/continents";
+    const VERSION: &'static str = "2023-03-31T00:00:00.000Z";
+}
+
+impl Endpoint for Region {
+    const AUTHENTICATED: bool = false;
+    const LOCALE: bool = true;
+    const NAME: &'static str = "v2/continents/:id/floors/:floor/regions";
+    const URL: &'static str = "v2/continents";
+    const VERSION: &'static str = "2023-03-31T00:00:00.000Z";
+}
+
+impl ParentEndpoint for Region {
+    type ParentId = ContinentFloorId;
+
+    const PATH: &'static str = "regions";
+
+    fn format_parent(id: &Self::ParentId) -> String {
+        id.to_string()
+    }
+}
+
+impl ParentBulkEndpoint for Region {
+    type IdType = RegionId;
+
+    const ALL: bool = true;
+
+    fn id(&self) -> &Self::IdType {
+        &self.id
+    }
+}
+
+impl Endpoint for Map {
+    const AUTHENTICATED: bool = false;
+    const LOCALE: bool = true;
+    const NAME: &'static str = "v2/continents/:id/floors/:floor/regions/:region/maps";
     const URL: &'static str = "v2/continents";
     const VERSION: &'static str = "2023-03-31T00:00:00.000Z";
 }
+
+impl ParentEndpoint for Map {
+    type ParentId = ContinentFloorRegionId;
+
+    const PATH: &'static str = "maps";
+
+    fn format_parent(id: &Self::ParentId) -> String {
+        id.to_string()
+    }
+}
+
+impl ParentBulkEndpoint for Map {
+    type IdType = MapId;
+
+    const ALL: bool = true;
+
+    fn id(&self) -> &Self::IdType {
+        &self.id
+    }
+}
@@ -1,2 +1,4 @@
 mod commerce;
+mod exchange;
 pub use commerce::*;
+pub use exchange::*;
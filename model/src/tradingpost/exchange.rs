@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Endpoint, QueryEndpoint};
+
+/// the amount to exchange, see [`CoinsToGems`] and [`GemsToCoins`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExchangeQuery {
+    pub quantity: u64,
+}
+
+/// the gems received for `quantity` coins
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub struct CoinsToGems {
+    pub coins_per_gem: u64,
+    /// gems received
+    pub quantity: u64,
+}
+
+impl Endpoint for CoinsToGems {
+    const AUTHENTICATED: bool = false;
+    const LOCALE: bool = false;
+    const URL: &'static str = "v2/commerce/exchange/coins";
+    const VERSION: &'static str = "2023-07-01T00:00:00.000Z";
+}
+
+impl QueryEndpoint for CoinsToGems {
+    type Query = ExchangeQuery;
+}
+
+/// the coins received for `quantity` gems
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, serde(deny_unknown_fields))]
+pub struct GemsToCoins {
+    pub coins_per_gem: u64,
+    /// coins received
+    pub quantity: u64,
+}
+
+impl Endpoint for GemsToCoins {
+    const AUTHENTICATED: bool = false;
+    const LOCALE: bool = false;
+    const URL: &'static str = "v2/commerce/exchange/gems";
+    const VERSION: &'static str = "2023-07-01T00:00:00.000Z";
+}
+
+impl QueryEndpoint for GemsToCoins {
+    type Query = ExchangeQuery;
+}